@@ -43,7 +43,7 @@ impl_colour_operator!(Add, add, +);
 impl_colour_operator!(Sub, sub, -);
 impl_colour_operator!(Mul, mul, *);
 
-impl<'a> Mul<f64> for &'a Colour {
+impl Mul<f64> for &Colour {
     type Output = Colour;
 
     fn mul(self, scalar: f64) -> Colour {
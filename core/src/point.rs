@@ -40,7 +40,7 @@ impl Point {
 }
 
 use std::ops::Add;
-impl<'a, 'b> Add<&'b Vector> for &'a Point {
+impl<'b> Add<&'b Vector> for &Point {
     type Output = Point;
 
     fn add(self, other: &'b Vector) -> Point {
@@ -50,7 +50,7 @@ impl<'a, 'b> Add<&'b Vector> for &'a Point {
 }
 
 use std::ops::Sub;
-impl<'a, 'b> Sub<&'b Point> for &'a Point {
+impl<'b> Sub<&'b Point> for &Point {
     type Output = Vector;
 
     fn sub(self, other: &'b Point) -> Vector {
@@ -59,7 +59,7 @@ impl<'a, 'b> Sub<&'b Point> for &'a Point {
     }
 }
 
-impl<'a, 'b> Sub<&'b Vector> for &'a Point {
+impl<'b> Sub<&'b Vector> for &Point {
     type Output = Point;
 
     fn sub(self, other: &'b Vector) -> Point {
@@ -33,7 +33,7 @@ implement_operations!(Add, add);
 implement_operations!(Sub, sub);
 implement_operations!(Mul, mul);
 
-impl<'a, const N: usize> Mul<f64> for &'a Tuple<N> {
+impl<const N: usize> Mul<f64> for &Tuple<N> {
     type Output = Tuple<N>;
 
     fn mul(self, scalar: f64) -> Self::Output {
@@ -46,7 +46,7 @@ impl<'a, const N: usize> Mul<f64> for &'a Tuple<N> {
 }
 
 use std::ops::Div;
-impl<'a, const N: usize> Div<f64> for &'a Tuple<N> {
+impl<const N: usize> Div<f64> for &Tuple<N> {
     type Output = Tuple<N>;
 
     fn div(self, scalar: f64) -> Self::Output {
@@ -55,7 +55,7 @@ impl<'a, const N: usize> Div<f64> for &'a Tuple<N> {
 }
 
 use std::ops::Neg;
-impl<'a, const N: usize> Neg for &'a Tuple<N> {
+impl<const N: usize> Neg for &Tuple<N> {
     type Output = Tuple<N>;
 
     fn neg(self) -> Self::Output {
@@ -102,7 +102,7 @@ implement_vector_scalar_operations!(Div, div);
 implement_vector_scalar_operations!(Mul, mul);
 
 use std::ops::Neg;
-impl<'a> Neg for &'a Vector {
+impl Neg for &Vector {
     type Output = Vector;
 
     fn neg(self) -> Vector {
@@ -74,7 +74,7 @@ macro_rules! mul_matrix {
 mul_matrix!(Point, Point);
 mul_matrix!(Vector, Vector);
 
-impl<'a, 'b> Mul<&'b Matrix4> for &'a Matrix4 {
+impl<'b> Mul<&'b Matrix4> for &Matrix4 {
     type Output = Matrix4;
 
     fn mul(self, other: &'b Matrix4) -> Matrix4 {
@@ -39,7 +39,7 @@ mod test {
         fn access() {
             let origin = Point::new(1.0, 2.0, 3.0);
             let direction = Vector::new(4.0, 5.0, 6.0);
-            let ray = Ray::new(origin.clone(), direction.clone());
+            let ray = Ray::new(origin, direction);
 
             assert_eq!(ray.origin, origin);
             assert_eq!(ray.direction, direction);
@@ -70,7 +70,7 @@ impl SquareMatrix<4> {
 
     fn cofactor(&self, row: usize, col: usize) -> f64 {
         let minor = self.minor(row, col);
-        if (row + col) % 2 == 0 {
+        if (row + col).is_multiple_of(2) {
             minor
         } else {
             -minor
@@ -131,7 +131,7 @@ impl SquareMatrix<3> {
 
     fn cofactor(&self, row: usize, col: usize) -> f64 {
         let minor = self.minor(row, col);
-        if (row + col) % 2 == 0 {
+        if (row + col).is_multiple_of(2) {
             minor
         } else {
             -minor
@@ -185,7 +185,7 @@ impl SquareMatrix<2> {
     #[allow(dead_code)]
     fn cofactor(&self, row: usize, col: usize) -> f64 {
         let minor = self.minor(row, col);
-        if (row + col) % 2 == 0 {
+        if (row + col).is_multiple_of(2) {
             minor
         } else {
             -minor
@@ -236,7 +236,7 @@ impl<const N: usize> PartialEq for SquareMatrix<N> {
 }
 
 use std::ops::Mul;
-impl<'a, 'b, const N: usize> Mul<&'b SquareMatrix<N>> for &'a SquareMatrix<N> {
+impl<'b, const N: usize> Mul<&'b SquareMatrix<N>> for &SquareMatrix<N> {
     type Output = SquareMatrix<N>;
 
     fn mul(self, other: &'b SquareMatrix<N>) -> SquareMatrix<N> {
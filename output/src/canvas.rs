@@ -53,7 +53,7 @@ mod tests {
         fn write_and_read_valid_pixel() {
             let mut canvas = Canvas::new(10, 10);
             let red = Colour::new(1.0, 0.0, 0.0);
-            let result = canvas.write_pixel(5, 5, red.clone());
+            let result = canvas.write_pixel(5, 5, red);
             assert!(result.is_ok());
             assert_eq!(canvas.pixel_at(5, 5).unwrap(), &red);
         }
@@ -80,9 +80,9 @@ mod tests {
             let green = Colour::new(0.0, 1.0, 0.0);
             let blue = Colour::new(0.0, 0.0, 1.0);
 
-            assert!(canvas.write_pixel(2, 2, red.clone()).is_ok());
-            assert!(canvas.write_pixel(5, 5, green.clone()).is_ok());
-            assert!(canvas.write_pixel(8, 8, blue.clone()).is_ok());
+            assert!(canvas.write_pixel(2, 2, red).is_ok());
+            assert!(canvas.write_pixel(5, 5, green).is_ok());
+            assert!(canvas.write_pixel(8, 8, blue).is_ok());
 
             assert_eq!(*canvas.pixel_at(2, 2).unwrap(), red);
             assert_eq!(*canvas.pixel_at(5, 5).unwrap(), green);
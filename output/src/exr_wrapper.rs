@@ -0,0 +1,336 @@
+use crate::canvas::Canvas;
+use crate::zlib;
+
+const MAGIC: [u8; 4] = [0x76, 0x2F, 0x31, 0x01];
+const VERSION: [u8; 4] = [2, 0, 0, 0];
+const PIXEL_TYPE_FLOAT: i32 = 2;
+// Channels must be listed, and stored, in alphabetical order.
+const CHANNELS: [&str; 3] = ["B", "G", "R"];
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ExrCompression {
+    /// Raw little-endian samples, one scanline per chunk.
+    None,
+    /// Blocks of 16 scanlines, reordered and delta encoded before deflating.
+    Zip,
+}
+
+impl ExrCompression {
+    fn id(&self) -> u8 {
+        match self {
+            ExrCompression::None => 0,
+            ExrCompression::Zip => 3,
+        }
+    }
+
+    fn scanlines_per_chunk(&self) -> usize {
+        match self {
+            ExrCompression::None => 1,
+            ExrCompression::Zip => 16,
+        }
+    }
+}
+
+/// A single-part scanline OpenEXR image with 32-bit float R, G and B channels.
+pub struct ExrWrapper {
+    canvas: Canvas,
+    compression: ExrCompression,
+}
+
+impl ExrWrapper {
+    pub fn new(canvas: Canvas, compression: ExrCompression) -> ExrWrapper {
+        ExrWrapper {
+            canvas,
+            compression,
+        }
+    }
+
+    pub fn height(&self) -> usize {
+        self.canvas.height
+    }
+
+    pub fn width(&self) -> usize {
+        self.canvas.width
+    }
+
+    pub fn to_exr(&self) -> Vec<u8> {
+        let mut exr = MAGIC.to_vec();
+        exr.extend(VERSION);
+        exr.extend(self.generate_header());
+
+        let chunks: Vec<Vec<u8>> = (0..self.height())
+            .step_by(self.compression.scanlines_per_chunk())
+            .map(|y| self.generate_chunk(y))
+            .collect();
+
+        // The offset table gives each chunk's absolute position in the file.
+        let mut offset = exr.len() + 8 * chunks.len();
+        for chunk in &chunks {
+            exr.extend((offset as u64).to_le_bytes());
+            offset += chunk.len();
+        }
+        for chunk in chunks {
+            exr.extend(chunk);
+        }
+        exr
+    }
+
+    fn generate_header(&self) -> Vec<u8> {
+        let mut channels = Vec::new();
+        for name in CHANNELS {
+            channels.extend(name.as_bytes());
+            channels.push(0);
+            channels.extend(PIXEL_TYPE_FLOAT.to_le_bytes());
+            // pLinear and three reserved bytes, then x and y sampling.
+            channels.extend([0, 0, 0, 0]);
+            channels.extend(1i32.to_le_bytes());
+            channels.extend(1i32.to_le_bytes());
+        }
+        channels.push(0);
+
+        let mut window = Vec::with_capacity(16);
+        for bound in [0, 0, self.width() as i32 - 1, self.height() as i32 - 1] {
+            window.extend(bound.to_le_bytes());
+        }
+
+        let mut header = Vec::new();
+        write_attribute(&mut header, "channels", "chlist", &channels);
+        write_attribute(
+            &mut header,
+            "compression",
+            "compression",
+            &[self.compression.id()],
+        );
+        write_attribute(&mut header, "dataWindow", "box2i", &window);
+        write_attribute(&mut header, "displayWindow", "box2i", &window);
+        write_attribute(&mut header, "lineOrder", "lineOrder", &[0]);
+        write_attribute(
+            &mut header,
+            "pixelAspectRatio",
+            "float",
+            &1f32.to_le_bytes(),
+        );
+        write_attribute(&mut header, "screenWindowCenter", "v2f", &[0; 8]);
+        write_attribute(
+            &mut header,
+            "screenWindowWidth",
+            "float",
+            &1f32.to_le_bytes(),
+        );
+        header.push(0);
+        header
+    }
+
+    /// The chunk starting at scanline `y`: its y coordinate, the size of its
+    /// pixel data, then the data itself.
+    fn generate_chunk(&self, y: usize) -> Vec<u8> {
+        let last = (y + self.compression.scanlines_per_chunk()).min(self.height());
+        let samples: Vec<u8> = (y..last).flat_map(|y| self.scanline(y)).collect();
+
+        let data = match self.compression {
+            ExrCompression::None => samples,
+            ExrCompression::Zip => {
+                let compressed = zlib::compress(&predict(&interleave(&samples)));
+                // Readers treat a chunk that is not smaller than its pixels as stored.
+                if compressed.len() < samples.len() {
+                    compressed
+                } else {
+                    samples
+                }
+            }
+        };
+
+        let mut chunk = Vec::with_capacity(8 + data.len());
+        chunk.extend((y as i32).to_le_bytes());
+        chunk.extend((data.len() as i32).to_le_bytes());
+        chunk.extend(data);
+        chunk
+    }
+
+    /// Every blue sample in the row, then every green, then every red.
+    fn scanline(&self, y: usize) -> Vec<u8> {
+        let mut scanline = Vec::with_capacity(self.width() * CHANNELS.len() * 4);
+        for channel in CHANNELS {
+            for x in 0..self.width() {
+                let pixel = self.canvas.pixel_at(x, y).unwrap();
+                let value = match channel {
+                    "B" => pixel.blue(),
+                    "G" => pixel.green(),
+                    _ => pixel.red(),
+                };
+                scanline.extend((value as f32).to_le_bytes());
+            }
+        }
+        scanline
+    }
+}
+
+fn write_attribute(header: &mut Vec<u8>, name: &str, kind: &str, value: &[u8]) {
+    header.extend(name.as_bytes());
+    header.push(0);
+    header.extend(kind.as_bytes());
+    header.push(0);
+    header.extend((value.len() as i32).to_le_bytes());
+    header.extend(value);
+}
+
+/// Moves the even-indexed bytes to the first half and the odd-indexed bytes to
+/// the second, so that the similar high bytes of neighbouring samples line up.
+fn interleave(bytes: &[u8]) -> Vec<u8> {
+    bytes
+        .iter()
+        .step_by(2)
+        .chain(bytes.iter().skip(1).step_by(2))
+        .copied()
+        .collect()
+}
+
+/// Replaces each byte after the first with its difference from the previous
+/// one, offset by 128.
+fn predict(bytes: &[u8]) -> Vec<u8> {
+    let mut previous = 0;
+    bytes
+        .iter()
+        .enumerate()
+        .map(|(i, byte)| {
+            let delta = if i == 0 {
+                *byte
+            } else {
+                byte.wrapping_sub(previous).wrapping_add(128)
+            };
+            previous = *byte;
+            delta
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::exr_wrapper::{interleave, predict, ExrCompression, ExrWrapper};
+    use crate::{zlib, Canvas};
+    use core::Colour;
+
+    fn gradient(width: usize, height: usize) -> Canvas {
+        let mut canvas = Canvas::new(width, height);
+        for y in 0..height {
+            for x in 0..width {
+                let colour = Colour::new(x as f64 * 4.0, y as f64 / 8.0, -0.5);
+                canvas.write_pixel(x, y, colour).unwrap();
+            }
+        }
+        canvas
+    }
+
+    fn read_u64(bytes: &[u8], at: usize) -> usize {
+        u64::from_le_bytes(bytes[at..at + 8].try_into().unwrap()) as usize
+    }
+
+    fn read_i32(bytes: &[u8], at: usize) -> i32 {
+        i32::from_le_bytes(bytes[at..at + 4].try_into().unwrap())
+    }
+
+    fn read_f32(bytes: &[u8], at: usize) -> f32 {
+        f32::from_le_bytes(bytes[at..at + 4].try_into().unwrap())
+    }
+
+    fn undo_predict_and_interleave(bytes: &[u8]) -> Vec<u8> {
+        let mut deltas = bytes.to_vec();
+        for i in 1..deltas.len() {
+            deltas[i] = deltas[i - 1].wrapping_add(deltas[i]).wrapping_sub(128);
+        }
+        let half = deltas.len().div_ceil(2);
+        (0..deltas.len())
+            .map(|i| {
+                if i % 2 == 0 {
+                    deltas[i / 2]
+                } else {
+                    deltas[half + i / 2]
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn exr_starts_with_magic_number_and_version() {
+        let exr = ExrWrapper::new(Canvas::new(2, 2), ExrCompression::None).to_exr();
+
+        assert_eq!(exr[..8], [0x76, 0x2F, 0x31, 0x01, 2, 0, 0, 0]);
+        assert_eq!(exr[8..17], *b"channels\0");
+    }
+
+    #[test]
+    fn header_describes_image() {
+        let header = ExrWrapper::new(Canvas::new(5, 3), ExrCompression::Zip).generate_header();
+        let find = |needle: &[u8]| {
+            header
+                .windows(needle.len())
+                .position(|window| window == needle)
+                .unwrap()
+                + needle.len()
+        };
+
+        assert_eq!(header[find(b"compression\0compression\0\x01\0\0\0")], 3);
+        let window = find(b"dataWindow\0box2i\0\x10\0\0\0");
+        assert_eq!(read_i32(&header, window + 8), 4);
+        assert_eq!(read_i32(&header, window + 12), 2);
+        assert_eq!(header.last(), Some(&0));
+    }
+
+    #[test]
+    fn uncompressed_samples_are_raw_floats() {
+        let exr = ExrWrapper::new(gradient(3, 2), ExrCompression::None).to_exr();
+        let header_end = 8 + ExrWrapper::new(Canvas::new(3, 2), ExrCompression::None)
+            .generate_header()
+            .len();
+
+        let second_line = read_u64(&exr, header_end + 8);
+        assert_eq!(read_i32(&exr, second_line), 1);
+        assert_eq!(read_i32(&exr, second_line + 4), 3 * 3 * 4);
+        let samples = second_line + 8;
+        // Blue, then green, then red, each for the whole line.
+        assert_eq!(read_f32(&exr, samples), -0.5);
+        assert_eq!(read_f32(&exr, samples + 12), 0.125);
+        assert_eq!(read_f32(&exr, samples + 32), 8.0);
+        assert_eq!(exr.len(), samples + 36);
+    }
+
+    #[test]
+    fn zip_chunks_hold_sixteen_scanlines() {
+        let canvas = gradient(40, 20);
+        let expected: Vec<u8> = (0..16)
+            .flat_map(|y| ExrWrapper::new(gradient(40, 20), ExrCompression::None).scanline(y))
+            .collect();
+        let exr = ExrWrapper::new(canvas, ExrCompression::Zip).to_exr();
+        let header_end = 8 + ExrWrapper::new(Canvas::new(40, 20), ExrCompression::Zip)
+            .generate_header()
+            .len();
+
+        let first = read_u64(&exr, header_end);
+        let second = read_u64(&exr, header_end + 8);
+        assert_eq!(first, header_end + 16);
+        assert_eq!(read_i32(&exr, second), 16);
+
+        let size = read_i32(&exr, first + 4) as usize;
+        assert!(size < expected.len());
+        let data = zlib::decompress(&exr[first + 8..first + 8 + size]).unwrap();
+        assert_eq!(undo_predict_and_interleave(&data), expected);
+    }
+
+    #[test]
+    fn incompressible_chunks_are_stored() {
+        let mut canvas = Canvas::new(1, 1);
+        canvas
+            .write_pixel(0, 0, Colour::new(0.1, 0.2, 0.3))
+            .unwrap();
+        let exr = ExrWrapper::new(canvas, ExrCompression::Zip).to_exr();
+
+        assert_eq!(read_i32(&exr, exr.len() - 16), 12);
+        assert_eq!(read_f32(&exr, exr.len() - 4), 0.1);
+    }
+
+    #[test]
+    fn preprocessing_for_zip() {
+        assert_eq!(interleave(&[1, 2, 3, 4, 5]), vec![1, 3, 5, 2, 4]);
+        assert_eq!(predict(&[10, 12, 11, 11]), vec![10, 130, 127, 128]);
+    }
+}
@@ -1,11 +1,23 @@
-use crate::ppm_wrapper::PpmWrapper;
+use crate::canvas::Canvas;
+use crate::exr_wrapper::{ExrCompression, ExrWrapper};
+use crate::hdr_wrapper::HdrWrapper;
+use crate::png_reader::read_png;
+use crate::png_wrapper::{BitDepth, PngWrapper, SIGNATURE};
+use crate::ppm_reader::read_ppm;
+use crate::ppm_wrapper::{PpmFormat, PpmWrapper};
+use crate::read_error::ImageReadError;
+use crate::tone_mapping::PostProcess;
 use std::io;
+use std::path::Path;
 
 pub trait Writable {
     fn open(filename: String) -> io::Result<Self>
     where
         Self: Sized;
     fn write_all(&mut self, buf: &[u8]) -> io::Result<()>;
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
 }
 
 impl Writable for std::fs::File {
@@ -15,39 +27,151 @@ impl Writable for std::fs::File {
     fn write_all(&mut self, buf: &[u8]) -> io::Result<()> {
         io::Write::write_all(self, buf)
     }
+    fn flush(&mut self) -> io::Result<()> {
+        io::Write::flush(self)
+    }
+}
+
+/// Lets a `Writable` be used wherever an `io::Write` is expected.
+struct WritableAdapter<W: Writable>(W);
+
+impl<W: Writable> io::Write for WritableAdapter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.write_all(buf)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.flush()
+    }
 }
 
-fn generate_filename(p: &PpmWrapper) -> String {
+fn generate_filename(width: usize, height: usize, extension: &str) -> String {
     use std::time::SystemTime;
     let timestamp = SystemTime::now()
         .duration_since(SystemTime::UNIX_EPOCH)
         .expect("Failed to obtain system time")
         .as_secs();
 
-    format!("{}_{}_{}.ppm", p.width(), p.height(), timestamp)
+    format!("{}_{}_{}.{}", width, height, timestamp, extension)
 }
 
+/// Writes the image as plain P3, the same bytes `PpmWrapper::to_ppm` produces.
 pub fn write_ppm<W: Writable>(ppm_wrapper: &PpmWrapper) -> io::Result<()> {
-    let mut file = W::open(generate_filename(ppm_wrapper))?;
-    file.write_all(ppm_wrapper.to_ppm().as_bytes())
+    write_ppm_streamed::<W>(ppm_wrapper, PpmFormat::Ascii)
+}
+
+/// Streams the image row by row through a buffer instead of building the whole
+/// file in memory first.
+pub fn write_ppm_streamed<W: Writable>(
+    ppm_wrapper: &PpmWrapper,
+    format: PpmFormat,
+) -> io::Result<()> {
+    let filename = generate_filename(ppm_wrapper.width(), ppm_wrapper.height(), "ppm");
+    let file = WritableAdapter(W::open(filename)?);
+    ppm_wrapper.write_to(&mut io::BufWriter::new(file), format)
+}
+
+pub fn write_png<W: Writable>(png_wrapper: &PngWrapper) -> io::Result<()> {
+    let filename = generate_filename(png_wrapper.width(), png_wrapper.height(), "png");
+    let mut file = W::open(filename)?;
+    file.write_all(&png_wrapper.to_png())
+}
+
+pub fn write_hdr<W: Writable>(hdr_wrapper: &HdrWrapper) -> io::Result<()> {
+    let filename = generate_filename(hdr_wrapper.width(), hdr_wrapper.height(), "hdr");
+    let mut file = W::open(filename)?;
+    file.write_all(&hdr_wrapper.to_hdr())
+}
+
+pub fn write_exr<W: Writable>(exr_wrapper: &ExrWrapper) -> io::Result<()> {
+    let filename = generate_filename(exr_wrapper.width(), exr_wrapper.height(), "exr");
+    let mut file = W::open(filename)?;
+    file.write_all(&exr_wrapper.to_exr())
+}
+
+/// Writes `canvas` to `filename` in the format named by its extension. PPM and
+/// PNG use 8-bit samples after applying `post_process`, and PPM is written as
+/// binary P6, streamed a row at a time. HDR and ZIP-compressed EXR keep the raw
+/// light as unclamped floats, so they ignore `post_process`.
+pub fn write_image<W: Writable>(
+    canvas: Canvas,
+    filename: &str,
+    post_process: &PostProcess,
+) -> io::Result<()> {
+    let extension = Path::new(filename)
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .map(|extension| extension.to_ascii_lowercase());
+
+    let bytes = match extension.as_deref() {
+        Some("ppm") => {
+            let file = WritableAdapter(W::open(filename.to_string())?);
+            return PpmWrapper::new(post_processed(canvas, post_process), 255)
+                .write_to(&mut io::BufWriter::new(file), PpmFormat::Binary);
+        }
+        Some("png") => {
+            PngWrapper::new(post_processed(canvas, post_process), BitDepth::Eight).to_png()
+        }
+        Some("hdr") => HdrWrapper::new(canvas).to_hdr(),
+        Some("exr") => ExrWrapper::new(canvas, ExrCompression::Zip).to_exr(),
+        _ => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Unsupported image format for {}", filename),
+            ))
+        }
+    };
+
+    let mut file = W::open(filename.to_string())?;
+    file.write_all(&bytes)
+}
+
+fn post_processed(canvas: Canvas, post_process: &PostProcess) -> Canvas {
+    if *post_process == PostProcess::default() {
+        canvas
+    } else {
+        post_process.apply(&canvas)
+    }
+}
+
+/// Loads a PPM or PNG file into a `Canvas`, recognising the format from its
+/// contents rather than its name.
+pub fn read_image(filename: &str) -> Result<Canvas, ImageReadError> {
+    decode_image(&std::fs::read(filename)?)
+}
+
+fn decode_image(bytes: &[u8]) -> Result<Canvas, ImageReadError> {
+    if bytes.starts_with(&SIGNATURE) {
+        read_png(bytes)
+    } else {
+        read_ppm(bytes)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::Canvas;
-    use crate::PpmWrapper;
+    use std::cell::RefCell;
     use std::io;
 
+    thread_local! {
+        // Everything written since the last `MockWritable` was opened, for
+        // functions that open their own file.
+        static WRITTEN: RefCell<Vec<u8>> = const { RefCell::new(Vec::new()) };
+    }
+
     struct MockWritable {
         buffer: Vec<u8>,
     }
 
     impl Writable for MockWritable {
         fn open(_filename: String) -> io::Result<Self> {
+            WRITTEN.with(|written| written.borrow_mut().clear());
             Ok(MockWritable { buffer: vec![] })
         }
         fn write_all(&mut self, buf: &[u8]) -> io::Result<()> {
+            WRITTEN.with(|written| written.borrow_mut().extend_from_slice(buf));
             self.buffer.extend_from_slice(buf);
             Ok(())
         }
@@ -55,14 +179,147 @@ mod tests {
 
     #[test]
     fn filename() {
-        let ppm_wrapper = PpmWrapper::new(Canvas::new(10, 10), 255);
-        let filename = generate_filename(&ppm_wrapper);
+        let filename = generate_filename(10, 10, "ppm");
         assert!(filename.starts_with("10_10_"));
         assert!(filename.ends_with(".ppm"));
     }
 
     #[test]
     fn write_ppm_to_mock() {
-        assert!(write_ppm::<MockWritable>(&PpmWrapper::new(Canvas::new(10, 10), 255)).is_ok());
+        let ppm_wrapper = PpmWrapper::new(Canvas::new(10, 10), 255);
+        assert!(write_ppm::<MockWritable>(&ppm_wrapper).is_ok());
+
+        let written = WRITTEN.with(|written| written.borrow().clone());
+        assert_eq!(written, ppm_wrapper.to_ppm().into_bytes());
+    }
+
+    #[test]
+    fn write_ppm_streamed_to_mock() {
+        let ppm_wrapper = PpmWrapper::new(Canvas::new(10, 10), 65535);
+        assert!(write_ppm_streamed::<MockWritable>(&ppm_wrapper, PpmFormat::Binary).is_ok());
+        assert!(write_ppm_streamed::<MockWritable>(&ppm_wrapper, PpmFormat::Ascii).is_ok());
+    }
+
+    #[test]
+    fn adapter_forwards_every_byte() {
+        let mut adapter = WritableAdapter(MockWritable { buffer: vec![] });
+        io::Write::write_all(&mut adapter, b"P6\n").unwrap();
+        io::Write::write_all(&mut adapter, &[0, 255]).unwrap();
+
+        assert_eq!(adapter.0.buffer, b"P6\n\x00\xff");
+    }
+
+    #[test]
+    fn adapter_forwards_flush_errors() {
+        struct FailingFlush;
+
+        impl Writable for FailingFlush {
+            fn open(_filename: String) -> io::Result<Self> {
+                Ok(FailingFlush)
+            }
+            fn write_all(&mut self, _buf: &[u8]) -> io::Result<()> {
+                Ok(())
+            }
+            fn flush(&mut self) -> io::Result<()> {
+                Err(io::Error::other("disk full"))
+            }
+        }
+
+        let error = write_ppm_streamed::<FailingFlush>(
+            &PpmWrapper::new(Canvas::new(2, 2), 255),
+            PpmFormat::Binary,
+        )
+        .unwrap_err();
+        assert_eq!(error.to_string(), "disk full");
+    }
+
+    #[test]
+    fn write_png_to_mock() {
+        let png_wrapper = PngWrapper::new(Canvas::new(10, 10), BitDepth::Sixteen);
+        assert!(write_png::<MockWritable>(&png_wrapper).is_ok());
+    }
+
+    #[test]
+    fn write_hdr_and_exr_to_mock() {
+        assert!(write_hdr::<MockWritable>(&HdrWrapper::new(Canvas::new(10, 10))).is_ok());
+        let exr_wrapper = ExrWrapper::new(Canvas::new(10, 10), ExrCompression::None);
+        assert!(write_exr::<MockWritable>(&exr_wrapper).is_ok());
+    }
+
+    #[test]
+    fn write_image_by_extension() {
+        let identity = PostProcess::default();
+        assert!(write_image::<MockWritable>(Canvas::new(2, 2), "render.ppm", &identity).is_ok());
+        assert!(write_image::<MockWritable>(Canvas::new(2, 2), "render.PNG", &identity).is_ok());
+        assert!(write_image::<MockWritable>(Canvas::new(2, 2), "render.hdr", &identity).is_ok());
+        assert!(write_image::<MockWritable>(Canvas::new(2, 2), "render.exr", &identity).is_ok());
+
+        let error =
+            write_image::<MockWritable>(Canvas::new(2, 2), "render.bmp", &identity).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+        assert!(write_image::<MockWritable>(Canvas::new(2, 2), "render", &identity).is_err());
+    }
+
+    #[test]
+    fn write_image_streams_binary_ppm() {
+        let mut canvas = Canvas::new(2, 1);
+        canvas
+            .write_pixel(1, 0, core::Colour::new(1.0, 0.5, 0.0))
+            .unwrap();
+        write_image::<MockWritable>(canvas, "render.ppm", &PostProcess::default()).unwrap();
+
+        let written = WRITTEN.with(|written| written.borrow().clone());
+        assert_eq!(written, b"P6\n2 1\n255\n\x00\x00\x00\xff\x80\x00");
+    }
+
+    #[test]
+    fn write_image_applies_the_post_process() {
+        let mut canvas = Canvas::new(1, 1);
+        canvas
+            .write_pixel(0, 0, core::Colour::new(0.25, 0.5, 2.0))
+            .unwrap();
+        let post_process = PostProcess::default().with_exposure(1.0);
+        write_image::<MockWritable>(canvas, "render.ppm", &post_process).unwrap();
+
+        let written = WRITTEN.with(|written| written.borrow().clone());
+        assert_eq!(written[written.len() - 3..], [128, 255, 255]);
+    }
+
+    #[test]
+    fn write_image_keeps_raw_light_for_exr() {
+        let canvas = || {
+            let mut canvas = Canvas::new(1, 1);
+            canvas
+                .write_pixel(0, 0, core::Colour::new(4.0, 0.5, 0.01))
+                .unwrap();
+            canvas
+        };
+        let post_process = PostProcess::display().with_exposure(1.0);
+        write_image::<MockWritable>(canvas(), "render.exr", &post_process).unwrap();
+
+        let written = WRITTEN.with(|written| written.borrow().clone());
+        assert_eq!(
+            written,
+            ExrWrapper::new(canvas(), ExrCompression::Zip).to_exr()
+        );
+    }
+
+    #[test]
+    fn decode_image_by_contents() {
+        let mut canvas = Canvas::new(1, 1);
+        canvas
+            .write_pixel(0, 0, core::Colour::new(1.0, 0.0, 0.0))
+            .unwrap();
+        let png = PngWrapper::new(canvas, BitDepth::Eight).to_png();
+
+        assert_eq!(
+            *decode_image(&png).unwrap().pixel_at(0, 0).unwrap(),
+            core::Colour::new(1.0, 0.0, 0.0)
+        );
+        assert!(decode_image(b"P3\n1 1\n1\n1 1 1\n").is_ok());
+        assert!(matches!(
+            read_image("does/not/exist.png"),
+            Err(ImageReadError::Io(_))
+        ));
     }
 }
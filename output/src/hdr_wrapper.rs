@@ -0,0 +1,119 @@
+use crate::canvas::Canvas;
+use core::Colour;
+
+pub struct HdrWrapper {
+    canvas: Canvas,
+}
+
+impl HdrWrapper {
+    pub fn new(canvas: Canvas) -> HdrWrapper {
+        HdrWrapper { canvas }
+    }
+
+    pub fn height(&self) -> usize {
+        self.canvas.height
+    }
+
+    pub fn width(&self) -> usize {
+        self.canvas.width
+    }
+
+    /// A Radiance picture with one flat (not run-length encoded) RGBE pixel per
+    /// four bytes, top row first.
+    pub fn to_hdr(&self) -> Vec<u8> {
+        let mut hdr = self.generate_header().into_bytes();
+        hdr.reserve(self.width() * self.height() * 4);
+        for y in 0..self.height() {
+            for x in 0..self.width() {
+                hdr.extend(rgbe(self.canvas.pixel_at(x, y).unwrap()));
+            }
+        }
+        hdr
+    }
+
+    fn generate_header(&self) -> String {
+        format!(
+            "#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n\n-Y {} +X {}\n",
+            self.height(),
+            self.width()
+        )
+    }
+}
+
+/// Shares a single exponent, taken from the brightest channel, between three
+/// 8-bit mantissas. Negative channels have no representation and become zero.
+fn rgbe(colour: &Colour) -> [u8; 4] {
+    let channels = [colour.red(), colour.green(), colour.blue()].map(|c| c.max(0.0));
+    let brightest = channels[0].max(channels[1]).max(channels[2]);
+    if brightest < 1e-32 || !brightest.is_finite() {
+        return [0; 4];
+    }
+
+    let (mantissa, exponent) = frexp(brightest);
+    let scale = mantissa * 256.0 / brightest;
+    let [red, green, blue] = channels.map(|c| (c * scale).min(255.0) as u8);
+    [red, green, blue, (exponent + 128).clamp(0, 255) as u8]
+}
+
+/// Splits a positive `value` into a mantissa in [0.5, 1) and a power of two.
+fn frexp(value: f64) -> (f64, i32) {
+    let mut exponent = value.log2().floor() as i32 + 1;
+    let mut mantissa = value / 2f64.powi(exponent);
+    // log2 can round either way for exact powers of two.
+    if mantissa >= 1.0 {
+        mantissa /= 2.0;
+        exponent += 1;
+    } else if mantissa < 0.5 {
+        mantissa *= 2.0;
+        exponent -= 1;
+    }
+    (mantissa, exponent)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::hdr_wrapper::{frexp, rgbe, HdrWrapper};
+    use crate::Canvas;
+    use core::Colour;
+
+    #[test]
+    fn hdr_header() {
+        let hdr = HdrWrapper::new(Canvas::new(5, 3)).to_hdr();
+        let header = b"#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n\n-Y 3 +X 5\n";
+
+        assert_eq!(hdr[..header.len()], header[..]);
+        assert_eq!(hdr.len(), header.len() + 5 * 3 * 4);
+    }
+
+    #[test]
+    fn splitting_into_mantissa_and_exponent() {
+        assert_eq!(frexp(1.0), (0.5, 1));
+        assert_eq!(frexp(0.75), (0.75, 0));
+        assert_eq!(frexp(12.0), (0.75, 4));
+        assert_eq!(frexp(0.125), (0.5, -2));
+    }
+
+    #[test]
+    fn encoding_rgbe_pixels() {
+        assert_eq!(rgbe(&Colour::new(0.0, 0.0, 0.0)), [0, 0, 0, 0]);
+        assert_eq!(rgbe(&Colour::new(1.0, 0.5, 0.25)), [128, 64, 32, 129]);
+        assert_eq!(rgbe(&Colour::new(-1.0, 0.5, 0.0)), [0, 128, 0, 128]);
+    }
+
+    #[test]
+    fn values_above_one_are_preserved() {
+        let mut canvas = Canvas::new(1, 1);
+        canvas
+            .write_pixel(0, 0, Colour::new(12.0, 3.0, 0.75))
+            .unwrap();
+        let hdr = HdrWrapper::new(canvas).to_hdr();
+
+        let [red, green, blue, exponent] = hdr[hdr.len() - 4..] else {
+            unreachable!()
+        };
+        let scale = 2f64.powi(exponent as i32 - 128 - 8);
+        assert_eq!(red as f64 * scale, 12.0);
+        assert_eq!(green as f64 * scale, 3.0);
+        assert_eq!(blue as f64 * scale, 0.75);
+    }
+}
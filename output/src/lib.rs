@@ -1,7 +1,24 @@
 mod canvas;
+mod exr_wrapper;
 mod file_helper;
+mod hdr_wrapper;
+mod png_reader;
+mod png_wrapper;
+mod ppm_reader;
 mod ppm_wrapper;
+mod read_error;
+mod tone_mapping;
+mod zlib;
 
 pub use crate::canvas::Canvas;
-pub use crate::file_helper::write_ppm;
-pub use crate::ppm_wrapper::PpmWrapper;
+pub use crate::exr_wrapper::{ExrCompression, ExrWrapper};
+pub use crate::file_helper::{
+    read_image, write_exr, write_hdr, write_image, write_png, write_ppm, write_ppm_streamed,
+};
+pub use crate::hdr_wrapper::HdrWrapper;
+pub use crate::png_reader::read_png;
+pub use crate::png_wrapper::{BitDepth, PngWrapper};
+pub use crate::ppm_reader::read_ppm;
+pub use crate::ppm_wrapper::{PpmFormat, PpmWrapper};
+pub use crate::read_error::ImageReadError;
+pub use crate::tone_mapping::{PostProcess, ToneMapping, TransferFunction};
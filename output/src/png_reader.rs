@@ -0,0 +1,470 @@
+use crate::canvas::Canvas;
+use crate::png_wrapper::{paeth, SIGNATURE};
+use crate::read_error::ImageReadError;
+use crate::zlib;
+use core::Colour;
+
+// The PNG specification limits each dimension to what fits in a signed 32-bit
+// integer.
+const MAX_DIMENSION: usize = i32::MAX as usize;
+
+struct Header {
+    width: usize,
+    height: usize,
+    bit_depth: u8,
+    colour_type: u8,
+}
+
+impl Header {
+    fn parse(data: &[u8]) -> Result<Header, ImageReadError> {
+        if data.len() != 13 {
+            return Err(ImageReadError::Malformed(
+                "IHDR chunk must be 13 bytes".to_string(),
+            ));
+        }
+
+        let header = Header {
+            width: u32::from_be_bytes([data[0], data[1], data[2], data[3]]) as usize,
+            height: u32::from_be_bytes([data[4], data[5], data[6], data[7]]) as usize,
+            bit_depth: data[8],
+            colour_type: data[9],
+        };
+        let (compression, filter, interlace) = (data[10], data[11], data[12]);
+
+        let allowed_depths: &[u8] = match header.colour_type {
+            0 => &[1, 2, 4, 8, 16],
+            3 => &[1, 2, 4, 8],
+            2 | 4 | 6 => &[8, 16],
+            colour_type => {
+                return Err(ImageReadError::Malformed(format!(
+                    "unknown colour type {}",
+                    colour_type
+                )))
+            }
+        };
+        if !allowed_depths.contains(&header.bit_depth) {
+            return Err(ImageReadError::Malformed(format!(
+                "bit depth {} is not allowed for colour type {}",
+                header.bit_depth, header.colour_type
+            )));
+        }
+        if compression != 0 || filter != 0 {
+            return Err(ImageReadError::Malformed(
+                "unknown compression or filter method".to_string(),
+            ));
+        }
+        if interlace != 0 {
+            return Err(ImageReadError::Unsupported(
+                "interlaced PNG images".to_string(),
+            ));
+        }
+        if !(1..=MAX_DIMENSION).contains(&header.width)
+            || !(1..=MAX_DIMENSION).contains(&header.height)
+            || header.data_size().is_none()
+        {
+            return Err(ImageReadError::Malformed(format!(
+                "{}x{} is not a valid image size",
+                header.width, header.height
+            )));
+        }
+
+        Ok(header)
+    }
+
+    fn channels(&self) -> usize {
+        match self.colour_type {
+            0 | 3 => 1,
+            4 => 2,
+            2 => 3,
+            _ => 4,
+        }
+    }
+
+    fn bits_per_pixel(&self) -> usize {
+        self.channels() * self.bit_depth as usize
+    }
+
+    fn stride(&self) -> usize {
+        (self.width * self.bits_per_pixel()).div_ceil(8)
+    }
+
+    /// The length of the filtered image data, a filter type byte and `stride`
+    /// bytes per row, or `None` if the image is too large to address.
+    fn data_size(&self) -> Option<usize> {
+        self.width.checked_mul(self.height)?;
+        let stride = self.width.checked_mul(self.bits_per_pixel())?.div_ceil(8);
+        (stride + 1).checked_mul(self.height)
+    }
+}
+
+/// Reads a non-interlaced PNG image of any colour type and bit depth. Alpha
+/// channels are ignored.
+pub fn read_png(bytes: &[u8]) -> Result<Canvas, ImageReadError> {
+    if bytes.len() < SIGNATURE.len() {
+        return Err(ImageReadError::Truncated);
+    }
+    if bytes[..SIGNATURE.len()] != SIGNATURE {
+        return Err(ImageReadError::Malformed(
+            "missing PNG signature".to_string(),
+        ));
+    }
+
+    let mut header = None;
+    let mut palette = Vec::new();
+    let mut data = Vec::new();
+    let mut position = SIGNATURE.len();
+
+    loop {
+        let (chunk_type, chunk) = read_chunk(bytes, &mut position)?;
+
+        match (&chunk_type, &header) {
+            (b"IHDR", None) => header = Some(Header::parse(chunk)?),
+            (_, None) => {
+                return Err(ImageReadError::Malformed(
+                    "IHDR must be the first chunk".to_string(),
+                ))
+            }
+            (b"PLTE", _) => {
+                palette = chunk
+                    .chunks_exact(3)
+                    .map(|entry| {
+                        Colour::new(
+                            entry[0] as f64 / 255.0,
+                            entry[1] as f64 / 255.0,
+                            entry[2] as f64 / 255.0,
+                        )
+                    })
+                    .collect()
+            }
+            (b"IDAT", _) => data.extend_from_slice(chunk),
+            (b"IEND", _) => break,
+            _ if chunk_type[0].is_ascii_uppercase() => {
+                return Err(ImageReadError::Unsupported(format!(
+                    "critical chunk {}",
+                    String::from_utf8_lossy(&chunk_type)
+                )))
+            }
+            _ => {}
+        }
+    }
+
+    let header = header.ok_or(ImageReadError::Truncated)?;
+    let rows = unfilter(&header, &zlib::decompress(&data)?)?;
+    decode_pixels(&header, &rows, &palette)
+}
+
+fn read_chunk<'a>(
+    bytes: &'a [u8],
+    position: &mut usize,
+) -> Result<([u8; 4], &'a [u8]), ImageReadError> {
+    let header = bytes
+        .get(*position..*position + 8)
+        .ok_or(ImageReadError::Truncated)?;
+    let length = u32::from_be_bytes([header[0], header[1], header[2], header[3]]) as usize;
+    let chunk_type = [header[4], header[5], header[6], header[7]];
+
+    let start = *position + 8;
+    let data = bytes
+        .get(start..start + length)
+        .ok_or(ImageReadError::Truncated)?;
+    let crc = bytes
+        .get(start + length..start + length + 4)
+        .ok_or(ImageReadError::Truncated)?;
+
+    if zlib::crc32_update(zlib::crc32(&chunk_type), data).to_be_bytes() != crc {
+        return Err(ImageReadError::Malformed(format!(
+            "CRC mismatch in {} chunk",
+            String::from_utf8_lossy(&chunk_type)
+        )));
+    }
+
+    *position = start + length + 4;
+    Ok((chunk_type, data))
+}
+
+/// Reverses the per-scanline filters, returning the raw bytes of each row.
+fn unfilter(header: &Header, data: &[u8]) -> Result<Vec<Vec<u8>>, ImageReadError> {
+    let stride = header.stride();
+    let bpp = header.bits_per_pixel().div_ceil(8);
+    let size = header.data_size().ok_or_else(|| {
+        ImageReadError::Malformed(format!("{}x{} is too large", header.width, header.height))
+    })?;
+    if data.len() < size {
+        return Err(ImageReadError::Truncated);
+    }
+
+    let mut rows: Vec<Vec<u8>> = Vec::with_capacity(header.height);
+    let empty = vec![0; stride];
+
+    for scanline in data.chunks_exact(stride + 1).take(header.height) {
+        let filter = scanline[0];
+        let previous = rows.last().unwrap_or(&empty);
+        let mut row = scanline[1..].to_vec();
+
+        for i in 0..stride {
+            let left = if i >= bpp { row[i - bpp] } else { 0 };
+            let up = previous[i];
+            let up_left = if i >= bpp { previous[i - bpp] } else { 0 };
+
+            let predictor = match filter {
+                0 => 0,
+                1 => left,
+                2 => up,
+                3 => ((left as u16 + up as u16) / 2) as u8,
+                4 => paeth(left, up, up_left),
+                _ => {
+                    return Err(ImageReadError::Malformed(format!(
+                        "unknown filter type {}",
+                        filter
+                    )))
+                }
+            };
+            row[i] = row[i].wrapping_add(predictor);
+        }
+        rows.push(row);
+    }
+    Ok(rows)
+}
+
+fn decode_pixels(
+    header: &Header,
+    rows: &[Vec<u8>],
+    palette: &[Colour],
+) -> Result<Canvas, ImageReadError> {
+    let max = ((1u32 << header.bit_depth) - 1) as f64;
+    let channels = header.channels();
+    let mut canvas = Canvas::new(header.width, header.height);
+
+    for (y, row) in rows.iter().enumerate() {
+        for x in 0..header.width {
+            let sample = |channel: usize| sample_at(row, x * channels + channel, header.bit_depth);
+
+            let colour = match header.colour_type {
+                0 | 4 => {
+                    let grey = sample(0) as f64 / max;
+                    Colour::new(grey, grey, grey)
+                }
+                3 => *palette.get(sample(0) as usize).ok_or_else(|| {
+                    ImageReadError::Malformed(format!(
+                        "palette index {} is out of range",
+                        sample(0)
+                    ))
+                })?,
+                _ => Colour::new(
+                    sample(0) as f64 / max,
+                    sample(1) as f64 / max,
+                    sample(2) as f64 / max,
+                ),
+            };
+            canvas
+                .write_pixel(x, y, colour)
+                .map_err(ImageReadError::Malformed)?;
+        }
+    }
+    Ok(canvas)
+}
+
+/// The `index`th sample of a row, where samples narrower than a byte are packed
+/// from the most significant bit.
+fn sample_at(row: &[u8], index: usize, bit_depth: u8) -> u16 {
+    match bit_depth {
+        16 => u16::from_be_bytes([row[index * 2], row[index * 2 + 1]]),
+        8 => row[index] as u16,
+        _ => {
+            let bit = index * bit_depth as usize;
+            let shift = 8 - bit_depth as usize - bit % 8;
+            ((row[bit / 8] >> shift) & ((1 << bit_depth) - 1)) as u16
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::png_reader::read_png;
+    use crate::png_wrapper::{write_chunk, SIGNATURE};
+    use crate::read_error::ImageReadError;
+    use crate::{zlib, BitDepth, Canvas, PngWrapper};
+    use core::Colour;
+
+    fn png(header: [u8; 13], chunks: &[(&[u8; 4], Vec<u8>)], scanlines: &[u8]) -> Vec<u8> {
+        let mut png = SIGNATURE.to_vec();
+        write_chunk(&mut png, b"IHDR", &header);
+        for (chunk_type, data) in chunks {
+            write_chunk(&mut png, chunk_type, data);
+        }
+        write_chunk(&mut png, b"IDAT", &zlib::compress(scanlines));
+        write_chunk(&mut png, b"IEND", &[]);
+        png
+    }
+
+    fn header(width: u8, height: u8, bit_depth: u8, colour_type: u8) -> [u8; 13] {
+        [
+            0,
+            0,
+            0,
+            width,
+            0,
+            0,
+            0,
+            height,
+            bit_depth,
+            colour_type,
+            0,
+            0,
+            0,
+        ]
+    }
+
+    fn gradient(width: usize, height: usize) -> Canvas {
+        let mut canvas = Canvas::new(width, height);
+        for y in 0..height {
+            for x in 0..width {
+                let colour = Colour::new(
+                    x as f64 / (width - 1) as f64,
+                    y as f64 / (height - 1) as f64,
+                    0.5,
+                );
+                canvas.write_pixel(x, y, colour).unwrap();
+            }
+        }
+        canvas
+    }
+
+    mod round_trip {
+        use super::*;
+
+        #[test]
+        fn sixteen_bit() {
+            let png = PngWrapper::new(gradient(16, 9), BitDepth::Sixteen).to_png();
+            let canvas = read_png(&png).unwrap();
+
+            let expected = gradient(16, 9);
+            for (x, y) in [(0, 0), (7, 3), (15, 8)] {
+                assert_eq!(canvas.pixel_at(x, y), expected.pixel_at(x, y));
+            }
+        }
+
+        #[test]
+        fn eight_bit() {
+            let png = PngWrapper::new(gradient(16, 9), BitDepth::Eight).to_png();
+            let canvas = read_png(&png).unwrap();
+
+            assert_eq!((canvas.width, canvas.height), (16, 9));
+            assert_eq!(
+                *canvas.pixel_at(15, 8).unwrap(),
+                Colour::new(1.0, 1.0, 128.0 / 255.0)
+            );
+        }
+    }
+
+    mod colour_types {
+        use super::*;
+
+        #[test]
+        fn packed_greyscale() {
+            // Two-bit samples 0, 1, 2 and 3 packed into a single byte.
+            let png = png(header(4, 1, 2, 0), &[], &[0, 0b00_01_10_11]);
+            let canvas = read_png(&png).unwrap();
+
+            for (x, grey) in [0.0, 1.0 / 3.0, 2.0 / 3.0, 1.0].iter().enumerate() {
+                assert_eq!(
+                    *canvas.pixel_at(x, 0).unwrap(),
+                    Colour::new(*grey, *grey, *grey)
+                );
+            }
+        }
+
+        #[test]
+        fn palette() {
+            let palette = vec![255, 0, 0, 0, 0, 255];
+            let png = png(header(3, 1, 4, 3), &[(b"PLTE", palette)], &[0, 0x10, 0x10]);
+            let canvas = read_png(&png).unwrap();
+
+            assert_eq!(*canvas.pixel_at(0, 0).unwrap(), Colour::new(0.0, 0.0, 1.0));
+            assert_eq!(*canvas.pixel_at(1, 0).unwrap(), Colour::new(1.0, 0.0, 0.0));
+            assert_eq!(*canvas.pixel_at(2, 0).unwrap(), Colour::new(0.0, 0.0, 1.0));
+        }
+
+        #[test]
+        fn alpha_is_ignored() {
+            let png = png(header(1, 1, 8, 6), &[], &[0, 255, 0, 51, 0]);
+            let canvas = read_png(&png).unwrap();
+
+            assert_eq!(*canvas.pixel_at(0, 0).unwrap(), Colour::new(1.0, 0.0, 0.2));
+        }
+
+        #[test]
+        fn palette_index_out_of_range() {
+            let png = png(header(1, 1, 8, 3), &[(b"PLTE", vec![0, 0, 0])], &[0, 1]);
+
+            assert!(matches!(read_png(&png), Err(ImageReadError::Malformed(_))));
+        }
+    }
+
+    mod errors {
+        use super::*;
+
+        #[test]
+        fn truncated_file() {
+            let png = PngWrapper::new(gradient(4, 4), BitDepth::Eight).to_png();
+
+            assert!(matches!(
+                read_png(&png[..5]),
+                Err(ImageReadError::Truncated)
+            ));
+            assert!(matches!(
+                read_png(&png[..png.len() - 20]),
+                Err(ImageReadError::Truncated)
+            ));
+        }
+
+        #[test]
+        fn missing_scanlines() {
+            let png = png(header(2, 2, 8, 2), &[], &[0, 1, 2, 3, 4, 5, 6]);
+
+            assert!(matches!(read_png(&png), Err(ImageReadError::Truncated)));
+        }
+
+        #[test]
+        fn corrupted_chunk() {
+            let mut png = PngWrapper::new(gradient(4, 4), BitDepth::Eight).to_png();
+            png[20] ^= 0xFF;
+
+            assert!(matches!(read_png(&png), Err(ImageReadError::Malformed(_))));
+        }
+
+        #[test]
+        fn invalid_dimensions() {
+            let mut huge = header(0, 0, 16, 6);
+            huge[..8].fill(0xFF);
+            let empty = header(0, 1, 8, 2);
+
+            for header in [huge, empty] {
+                assert!(matches!(
+                    read_png(&png(header, &[], &[0])),
+                    Err(ImageReadError::Malformed(_))
+                ));
+            }
+        }
+
+        #[test]
+        fn not_a_png() {
+            assert!(matches!(
+                read_png(b"P3\n1 1\n255\n0 0 0\n"),
+                Err(ImageReadError::Malformed(_))
+            ));
+        }
+
+        #[test]
+        fn interlaced_images_are_unsupported() {
+            let mut header = header(1, 1, 8, 2);
+            header[12] = 1;
+            let png = png(header, &[], &[0, 0, 0, 0]);
+
+            assert!(matches!(
+                read_png(&png),
+                Err(ImageReadError::Unsupported(_))
+            ));
+        }
+    }
+}
@@ -0,0 +1,242 @@
+use crate::canvas::Canvas;
+use crate::zlib;
+
+pub(crate) const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1A, b'\n'];
+const COLOUR_TYPE_RGB: u8 = 2;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum BitDepth {
+    Eight,
+    Sixteen,
+}
+
+impl BitDepth {
+    fn bits(&self) -> u8 {
+        match self {
+            BitDepth::Eight => 8,
+            BitDepth::Sixteen => 16,
+        }
+    }
+
+    fn bytes_per_pixel(&self) -> usize {
+        3 * self.bits() as usize / 8
+    }
+}
+
+pub struct PngWrapper {
+    canvas: Canvas,
+    bit_depth: BitDepth,
+}
+
+impl PngWrapper {
+    pub fn new(canvas: Canvas, bit_depth: BitDepth) -> PngWrapper {
+        PngWrapper { canvas, bit_depth }
+    }
+
+    pub fn height(&self) -> usize {
+        self.canvas.height
+    }
+
+    pub fn width(&self) -> usize {
+        self.canvas.width
+    }
+
+    pub fn to_png(&self) -> Vec<u8> {
+        let mut png = SIGNATURE.to_vec();
+        write_chunk(&mut png, b"IHDR", &self.generate_header());
+        write_chunk(
+            &mut png,
+            b"IDAT",
+            &zlib::compress(&self.generate_scanlines()),
+        );
+        write_chunk(&mut png, b"IEND", &[]);
+        png
+    }
+
+    fn generate_header(&self) -> Vec<u8> {
+        let mut header = Vec::with_capacity(13);
+        header.extend((self.width() as u32).to_be_bytes());
+        header.extend((self.height() as u32).to_be_bytes());
+        // Bit depth, colour type, then default compression, filter and interlace methods.
+        header.extend([self.bit_depth.bits(), COLOUR_TYPE_RGB, 0, 0, 0]);
+        header
+    }
+
+    /// Each row of samples, filtered with whichever PNG filter gives the smallest
+    /// sum of absolute differences and prefixed by the filter's type byte.
+    fn generate_scanlines(&self) -> Vec<u8> {
+        let row_length = self.width() * self.bit_depth.bytes_per_pixel();
+        let mut scanlines = Vec::with_capacity((row_length + 1) * self.height());
+        let mut previous = vec![0; row_length];
+
+        for y in 0..self.height() {
+            let row = self.row_samples(y);
+            let (filter, filtered) = (0..5)
+                .map(|filter| {
+                    (
+                        filter,
+                        apply_filter(filter, &row, &previous, self.bit_depth),
+                    )
+                })
+                .min_by_key(|(_, filtered)| {
+                    filtered
+                        .iter()
+                        .map(|byte| (*byte as i8).unsigned_abs() as usize)
+                        .sum::<usize>()
+                })
+                .expect("there is always at least one filter");
+
+            scanlines.push(filter);
+            scanlines.extend(filtered);
+            previous = row;
+        }
+        scanlines
+    }
+
+    fn row_samples(&self, y: usize) -> Vec<u8> {
+        let mut row = Vec::with_capacity(self.width() * self.bit_depth.bytes_per_pixel());
+        for x in 0..self.width() {
+            let pixel = self.canvas.pixel_at(x, y).unwrap();
+            for channel in [pixel.red(), pixel.green(), pixel.blue()] {
+                match self.bit_depth {
+                    BitDepth::Eight => row.push(scale_sample(channel, u8::MAX as u16) as u8),
+                    BitDepth::Sixteen => row.extend(scale_sample(channel, u16::MAX).to_be_bytes()),
+                }
+            }
+        }
+        row
+    }
+}
+
+fn scale_sample(colour_value: f64, max_value: u16) -> u16 {
+    (colour_value.clamp(0.0, 1.0) * max_value as f64).round() as u16
+}
+
+fn apply_filter(filter: u8, row: &[u8], previous: &[u8], bit_depth: BitDepth) -> Vec<u8> {
+    let bpp = bit_depth.bytes_per_pixel();
+
+    (0..row.len())
+        .map(|i| {
+            let left = if i >= bpp { row[i - bpp] } else { 0 };
+            let up = previous[i];
+            let up_left = if i >= bpp { previous[i - bpp] } else { 0 };
+
+            let predictor = match filter {
+                0 => 0,
+                1 => left,
+                2 => up,
+                3 => ((left as u16 + up as u16) / 2) as u8,
+                _ => paeth(left, up, up_left),
+            };
+            row[i].wrapping_sub(predictor)
+        })
+        .collect()
+}
+
+pub(crate) fn paeth(left: u8, up: u8, up_left: u8) -> u8 {
+    let estimate = left as i16 + up as i16 - up_left as i16;
+    let distance_left = (estimate - left as i16).abs();
+    let distance_up = (estimate - up as i16).abs();
+    let distance_up_left = (estimate - up_left as i16).abs();
+
+    if distance_left <= distance_up && distance_left <= distance_up_left {
+        left
+    } else if distance_up <= distance_up_left {
+        up
+    } else {
+        up_left
+    }
+}
+
+pub(crate) fn write_chunk(png: &mut Vec<u8>, chunk_type: &[u8; 4], data: &[u8]) {
+    png.extend((data.len() as u32).to_be_bytes());
+    png.extend(chunk_type);
+    png.extend(data);
+    png.extend(zlib::crc32_update(zlib::crc32(chunk_type), data).to_be_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::png_wrapper::{paeth, BitDepth, PngWrapper};
+    use crate::Canvas;
+    use core::Colour;
+
+    #[test]
+    fn png_starts_with_signature_and_header() {
+        let png = PngWrapper::new(Canvas::new(5, 3), BitDepth::Eight).to_png();
+
+        assert_eq!(
+            png[..8],
+            [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1A, b'\n']
+        );
+        assert_eq!(png[8..16], [0, 0, 0, 13, b'I', b'H', b'D', b'R']);
+        assert_eq!(png[16..29], [0, 0, 0, 5, 0, 0, 0, 3, 8, 2, 0, 0, 0]);
+    }
+
+    #[test]
+    fn png_ends_with_image_end_chunk() {
+        let png = PngWrapper::new(Canvas::new(5, 3), BitDepth::Sixteen).to_png();
+
+        assert_eq!(png[24], 16);
+        assert_eq!(
+            png[png.len() - 12..],
+            [0, 0, 0, 0, b'I', b'E', b'N', b'D', 0xAE, 0x42, 0x60, 0x82]
+        );
+    }
+
+    #[test]
+    fn eight_bit_samples() {
+        let mut canvas = Canvas::new(2, 1);
+        canvas
+            .write_pixel(0, 0, Colour::new(1.5, 0.5, -0.5))
+            .unwrap();
+        canvas
+            .write_pixel(1, 0, Colour::new(0.0, 0.25, 1.0))
+            .unwrap();
+
+        assert_eq!(
+            PngWrapper::new(canvas, BitDepth::Eight).row_samples(0),
+            vec![255, 128, 0, 0, 64, 255]
+        );
+    }
+
+    #[test]
+    fn sixteen_bit_samples_are_big_endian() {
+        let mut canvas = Canvas::new(1, 1);
+        canvas
+            .write_pixel(0, 0, Colour::new(1.0, 0.5, 0.0))
+            .unwrap();
+
+        assert_eq!(
+            PngWrapper::new(canvas, BitDepth::Sixteen).row_samples(0),
+            vec![0xFF, 0xFF, 0x80, 0x00, 0x00, 0x00]
+        );
+    }
+
+    #[test]
+    fn each_scanline_starts_with_a_filter_type() {
+        let mut canvas = Canvas::new(3, 2);
+        for x in 0..3 {
+            canvas
+                .write_pixel(x, 0, Colour::new(0.2, 0.4, 0.6))
+                .unwrap();
+            canvas
+                .write_pixel(x, 1, Colour::new(0.2, 0.4, 0.6))
+                .unwrap();
+        }
+
+        let scanlines = PngWrapper::new(canvas, BitDepth::Eight).generate_scanlines();
+
+        assert_eq!(scanlines.len(), 2 * (1 + 9));
+        // The second row is identical to the first, so the Up filter zeroes it.
+        assert_eq!(scanlines[10..], [2, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn paeth_predictor() {
+        assert_eq!(paeth(10, 20, 10), 20);
+        assert_eq!(paeth(20, 10, 10), 20);
+        assert_eq!(paeth(10, 10, 20), 10);
+        assert_eq!(paeth(0, 0, 0), 0);
+    }
+}
@@ -0,0 +1,236 @@
+use crate::canvas::Canvas;
+use crate::read_error::ImageReadError;
+use core::Colour;
+
+/// Reads a plain (P3) or raw (P6) PPM image, scaling samples by the file's max
+/// colour value so that they fall between 0.0 and 1.0.
+pub fn read_ppm(bytes: &[u8]) -> Result<Canvas, ImageReadError> {
+    let mut reader = PpmReader { bytes, position: 0 };
+
+    let magic = reader.token()?;
+    if magic != b"P3" && magic != b"P6" {
+        return Err(ImageReadError::Unsupported(format!(
+            "PPM type {}",
+            String::from_utf8_lossy(magic)
+        )));
+    }
+
+    let width = reader.number("width")?;
+    let height = reader.number("height")?;
+    let max_colour_value = reader.number("max colour value")?;
+    if !(1..=65535).contains(&max_colour_value) {
+        return Err(ImageReadError::Malformed(format!(
+            "max colour value must be between 1 and 65535, got {}",
+            max_colour_value
+        )));
+    }
+
+    let sample_count = width
+        .checked_mul(height)
+        .and_then(|pixels| pixels.checked_mul(3))
+        .ok_or_else(|| ImageReadError::Malformed(format!("{}x{} is too large", width, height)))?;
+
+    let samples = if magic == b"P3" {
+        (0..sample_count)
+            .map(|_| reader.number("sample"))
+            .collect::<Result<Vec<_>, _>>()?
+    } else {
+        reader.single_whitespace()?;
+        reader.raw_samples(sample_count, max_colour_value > 255)?
+    };
+
+    if let Some(sample) = samples.iter().find(|sample| **sample > max_colour_value) {
+        return Err(ImageReadError::Malformed(format!(
+            "sample {} exceeds max colour value {}",
+            sample, max_colour_value
+        )));
+    }
+
+    let mut canvas = Canvas::new(width, height);
+    let scale = max_colour_value as f64;
+    for (i, pixel) in samples.chunks(3).enumerate() {
+        let colour = Colour::new(
+            pixel[0] as f64 / scale,
+            pixel[1] as f64 / scale,
+            pixel[2] as f64 / scale,
+        );
+        canvas
+            .write_pixel(i % width, i / width, colour)
+            .map_err(ImageReadError::Malformed)?;
+    }
+    Ok(canvas)
+}
+
+struct PpmReader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> PpmReader<'a> {
+    /// The next whitespace-separated token, skipping `#` comments.
+    fn token(&mut self) -> Result<&'a [u8], ImageReadError> {
+        loop {
+            match self.bytes.get(self.position) {
+                None => return Err(ImageReadError::Truncated),
+                Some(b'#') => {
+                    while self
+                        .bytes
+                        .get(self.position)
+                        .is_some_and(|byte| *byte != b'\n' && *byte != b'\r')
+                    {
+                        self.position += 1;
+                    }
+                }
+                Some(byte) if byte.is_ascii_whitespace() => self.position += 1,
+                Some(_) => break,
+            }
+        }
+
+        let start = self.position;
+        while self
+            .bytes
+            .get(self.position)
+            .is_some_and(|byte| !byte.is_ascii_whitespace() && *byte != b'#')
+        {
+            self.position += 1;
+        }
+        Ok(&self.bytes[start..self.position])
+    }
+
+    fn number(&mut self, name: &str) -> Result<usize, ImageReadError> {
+        let token = self.token()?;
+        std::str::from_utf8(token)
+            .ok()
+            .and_then(|token| token.parse().ok())
+            .ok_or_else(|| {
+                ImageReadError::Malformed(format!(
+                    "expected {} but found '{}'",
+                    name,
+                    String::from_utf8_lossy(token)
+                ))
+            })
+    }
+
+    /// The single whitespace byte separating a raw PPM header from its samples.
+    fn single_whitespace(&mut self) -> Result<(), ImageReadError> {
+        match self.bytes.get(self.position) {
+            None => Err(ImageReadError::Truncated),
+            Some(byte) if byte.is_ascii_whitespace() => {
+                self.position += 1;
+                Ok(())
+            }
+            Some(_) => Err(ImageReadError::Malformed(
+                "expected whitespace before raw samples".to_string(),
+            )),
+        }
+    }
+
+    fn raw_samples(&mut self, count: usize, wide: bool) -> Result<Vec<usize>, ImageReadError> {
+        let length = count.saturating_mul(if wide { 2 } else { 1 });
+        let data = self
+            .bytes
+            .get(self.position..self.position.saturating_add(length))
+            .ok_or(ImageReadError::Truncated)?;
+        self.position += length;
+
+        Ok(data
+            .chunks(if wide { 2 } else { 1 })
+            .map(|sample| match sample {
+                [high, low] => u16::from_be_bytes([*high, *low]) as usize,
+                _ => sample[0] as usize,
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ppm_reader::read_ppm;
+    use crate::read_error::ImageReadError;
+    use crate::{Canvas, PpmFormat, PpmWrapper};
+    use core::Colour;
+
+    #[test]
+    fn reading_plain_ppm_with_comments_and_whitespace() {
+        let ppm = b"P3\n# made by hand\n  2\t1 # width and height\n\n100\n\
+                    100 50 0\n0   25\n100\n";
+
+        let canvas = read_ppm(ppm).unwrap();
+
+        assert_eq!((canvas.width, canvas.height), (2, 1));
+        assert_eq!(*canvas.pixel_at(0, 0).unwrap(), Colour::new(1.0, 0.5, 0.0));
+        assert_eq!(*canvas.pixel_at(1, 0).unwrap(), Colour::new(0.0, 0.25, 1.0));
+    }
+
+    #[test]
+    fn reading_raw_ppm() {
+        let mut ppm = b"P6 # raw\n2 1\n255\n".to_vec();
+        ppm.extend([255, 0, 51, 0, 102, 255]);
+
+        let canvas = read_ppm(&ppm).unwrap();
+
+        assert_eq!(*canvas.pixel_at(0, 0).unwrap(), Colour::new(1.0, 0.0, 0.2));
+        assert_eq!(*canvas.pixel_at(1, 0).unwrap(), Colour::new(0.0, 0.4, 1.0));
+    }
+
+    #[test]
+    fn round_trip_through_wide_raw_ppm() {
+        let mut canvas = Canvas::new(3, 2);
+        canvas
+            .write_pixel(2, 1, Colour::new(0.25, 0.5, 0.75))
+            .unwrap();
+        let mut ppm = Vec::new();
+        PpmWrapper::new(canvas, 65535)
+            .write_to(&mut ppm, PpmFormat::Binary)
+            .unwrap();
+
+        let canvas = read_ppm(&ppm).unwrap();
+
+        assert_eq!(
+            *canvas.pixel_at(2, 1).unwrap(),
+            Colour::new(0.25, 0.5, 0.75)
+        );
+        assert_eq!(*canvas.pixel_at(0, 0).unwrap(), Colour::new(0.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn truncated_files() {
+        assert!(matches!(read_ppm(b""), Err(ImageReadError::Truncated)));
+        assert!(matches!(
+            read_ppm(b"P3\n2 1\n255\n255 0 0 0"),
+            Err(ImageReadError::Truncated)
+        ));
+        assert!(matches!(
+            read_ppm(b"P6\n2 1\n255\n\xff\x00\x00"),
+            Err(ImageReadError::Truncated)
+        ));
+    }
+
+    #[test]
+    fn malformed_files() {
+        assert!(matches!(
+            read_ppm(b"P3\n2 x\n255\n"),
+            Err(ImageReadError::Malformed(_))
+        ));
+        assert!(matches!(
+            read_ppm(b"P3\n1 1\n0\n0 0 0\n"),
+            Err(ImageReadError::Malformed(_))
+        ));
+        assert!(matches!(
+            read_ppm(b"P3\n1 1\n255\n256 0 0\n"),
+            Err(ImageReadError::Malformed(_))
+        ));
+    }
+
+    #[test]
+    fn other_netpbm_types_are_unsupported() {
+        assert!(matches!(
+            read_ppm(b"P5\n1 1\n255\n\x00"),
+            Err(ImageReadError::Unsupported(_))
+        ));
+        assert!(matches!(
+            read_ppm(b"\x89PNG\r\n"),
+            Err(ImageReadError::Unsupported(_))
+        ));
+    }
+}
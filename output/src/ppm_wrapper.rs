@@ -1,4 +1,23 @@
 use crate::canvas::Canvas;
+use std::io;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum PpmFormat {
+    /// Plain P3, with decimal samples wrapped at 70 columns.
+    Ascii,
+    /// Raw P6, with one byte per sample, or two big-endian bytes when
+    /// `max_colour_value` is over 255.
+    Binary,
+}
+
+impl PpmFormat {
+    fn magic_number(&self) -> &'static str {
+        match self {
+            PpmFormat::Ascii => "P3",
+            PpmFormat::Binary => "P6",
+        }
+    }
+}
 
 pub struct PpmWrapper {
     canvas: Canvas,
@@ -21,8 +40,13 @@ impl PpmWrapper {
     }
 
     fn generate_header(&self) -> String {
+        self.generate_header_for(PpmFormat::Ascii)
+    }
+
+    fn generate_header_for(&self, format: PpmFormat) -> String {
         format!(
-            "P3\n{} {}\n{}\n",
+            "{}\n{} {}\n{}\n",
+            format.magic_number(),
             self.width(),
             self.height(),
             self.max_colour_value
@@ -30,28 +54,73 @@ impl PpmWrapper {
     }
 
     fn generate_body(&self) -> String {
-        let mut body = String::new();
-        for y in 0..self.canvas.height {
-            let mut line = String::new();
-            for x in 0..self.canvas.width {
-                let pixel = self.canvas.pixel_at(x, y).unwrap();
-                let red = scale_colour_value(pixel.red(), self.max_colour_value);
-                let green = scale_colour_value(pixel.green(), self.max_colour_value);
-                let blue = scale_colour_value(pixel.blue(), self.max_colour_value);
+        (0..self.canvas.height)
+            .map(|y| self.generate_row(y))
+            .collect()
+    }
 
-                add_to_line_or_body(&mut body, &mut line, red);
-                add_to_line_or_body(&mut body, &mut line, green);
-                add_to_line_or_body(&mut body, &mut line, blue);
+    fn generate_row(&self, y: usize) -> String {
+        let mut row = String::new();
+        let mut line = String::new();
+        for [red, green, blue] in self.scaled_row(y) {
+            add_to_line_or_body(&mut row, &mut line, red);
+            add_to_line_or_body(&mut row, &mut line, green);
+            add_to_line_or_body(&mut row, &mut line, blue);
+        }
+        pop_trailing_space(&mut line);
+        row.push_str(&format!("{}\n", line));
+        row
+    }
+
+    fn generate_binary_row(&self, y: usize) -> Vec<u8> {
+        let wide = self.max_colour_value > 255;
+        let mut row = Vec::with_capacity(self.canvas.width * if wide { 6 } else { 3 });
+        for sample in self.scaled_row(y).into_iter().flatten() {
+            if wide {
+                row.extend((sample as u16).to_be_bytes());
+            } else {
+                row.push(sample as u8);
             }
-            pop_trailing_space(&mut line);
-            body.push_str(&format!("{}\n", line));
         }
-        body
+        row
+    }
+
+    fn scaled_row(&self, y: usize) -> Vec<[usize; 3]> {
+        (0..self.canvas.width)
+            .map(|x| {
+                let pixel = self.canvas.pixel_at(x, y).unwrap();
+                [pixel.red(), pixel.green(), pixel.blue()]
+                    .map(|channel| scale_colour_value(channel, self.max_colour_value))
+            })
+            .collect()
     }
 
     pub fn to_ppm(&self) -> String {
         format!("{}{}", self.generate_header(), self.generate_body())
     }
+
+    /// Streams the image into `writer` one row at a time, so the whole file is
+    /// never held in memory.
+    pub fn write_to<W: io::Write>(&self, writer: &mut W, format: PpmFormat) -> io::Result<()> {
+        if format == PpmFormat::Binary && !(1..=65535).contains(&self.max_colour_value) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "P6 requires a max colour value between 1 and 65535, got {}",
+                    self.max_colour_value
+                ),
+            ));
+        }
+
+        writer.write_all(self.generate_header_for(format).as_bytes())?;
+        for y in 0..self.canvas.height {
+            match format {
+                PpmFormat::Ascii => writer.write_all(self.generate_row(y).as_bytes())?,
+                PpmFormat::Binary => writer.write_all(&self.generate_binary_row(y))?,
+            }
+        }
+        writer.flush()
+    }
 }
 
 fn scale_colour_value(colour_value: f64, max_colour_value: usize) -> usize {
@@ -80,14 +149,14 @@ fn add_to_line_or_body(body: &mut String, line: &mut String, value: usize) {
 
 #[cfg(test)]
 mod tests {
-    use crate::ppm_wrapper::{scale_colour_value, PpmWrapper};
+    use crate::ppm_wrapper::{scale_colour_value, PpmFormat, PpmWrapper};
     use crate::Canvas;
     use core::Colour;
 
     fn fill_canvas_with_colour(canvas: &mut Canvas, colour: Colour) {
         for y in 0..canvas.height {
             for x in 0..canvas.width {
-                canvas.write_pixel(x, y, colour.clone()).unwrap();
+                canvas.write_pixel(x, y, colour).unwrap();
             }
         }
     }
@@ -165,4 +234,63 @@ mod tests {
         assert_eq!(scale_colour_value(-1.0, 255), 0);
         assert_eq!(scale_colour_value(1.5, 255), 255);
     }
+
+    #[test]
+    fn streamed_ascii_matches_to_ppm() {
+        let mut canvas = Canvas::new(10, 2);
+        fill_canvas_with_colour(&mut canvas, Colour::new(1.0, 0.8, 0.6));
+        let ppm_wrapper = PpmWrapper::new(canvas, 255);
+
+        let mut buffer = Vec::new();
+        ppm_wrapper.write_to(&mut buffer, PpmFormat::Ascii).unwrap();
+
+        assert_eq!(String::from_utf8(buffer).unwrap(), ppm_wrapper.to_ppm());
+    }
+
+    #[test]
+    fn binary_ppm_with_byte_samples() {
+        let mut canvas = Canvas::new(2, 1);
+        canvas
+            .write_pixel(0, 0, Colour::new(1.5, 0.5, 0.0))
+            .unwrap();
+        canvas
+            .write_pixel(1, 0, Colour::new(0.0, 0.8, 1.0))
+            .unwrap();
+
+        let mut buffer = Vec::new();
+        PpmWrapper::new(canvas, 255)
+            .write_to(&mut buffer, PpmFormat::Binary)
+            .unwrap();
+
+        let mut expected = b"P6\n2 1\n255\n".to_vec();
+        expected.extend([255, 128, 0, 0, 204, 255]);
+        assert_eq!(buffer, expected);
+    }
+
+    #[test]
+    fn binary_ppm_with_wide_samples() {
+        let mut canvas = Canvas::new(1, 1);
+        canvas
+            .write_pixel(0, 0, Colour::new(1.0, 0.5, 0.0))
+            .unwrap();
+
+        let mut buffer = Vec::new();
+        PpmWrapper::new(canvas, 65535)
+            .write_to(&mut buffer, PpmFormat::Binary)
+            .unwrap();
+
+        let mut expected = b"P6\n1 1\n65535\n".to_vec();
+        expected.extend([0xFF, 0xFF, 0x80, 0x00, 0x00, 0x00]);
+        assert_eq!(buffer, expected);
+    }
+
+    #[test]
+    fn binary_ppm_rejects_out_of_range_max_colour_value() {
+        let mut buffer = Vec::new();
+        let result =
+            PpmWrapper::new(Canvas::new(1, 1), 70000).write_to(&mut buffer, PpmFormat::Binary);
+
+        assert!(result.is_err());
+        assert!(buffer.is_empty());
+    }
 }
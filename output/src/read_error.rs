@@ -0,0 +1,32 @@
+use std::fmt;
+use std::io;
+
+#[derive(Debug)]
+pub enum ImageReadError {
+    /// The data ended before the image was complete.
+    Truncated,
+    /// The data is not a valid image of the expected format.
+    Malformed(String),
+    /// The image is valid but uses a feature this reader does not handle.
+    Unsupported(String),
+    Io(io::Error),
+}
+
+impl fmt::Display for ImageReadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImageReadError::Truncated => write!(f, "image data is truncated"),
+            ImageReadError::Malformed(message) => write!(f, "malformed image: {}", message),
+            ImageReadError::Unsupported(feature) => write!(f, "unsupported image: {}", feature),
+            ImageReadError::Io(error) => write!(f, "failed to read image: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for ImageReadError {}
+
+impl From<io::Error> for ImageReadError {
+    fn from(error: io::Error) -> Self {
+        ImageReadError::Io(error)
+    }
+}
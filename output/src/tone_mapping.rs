@@ -0,0 +1,231 @@
+use crate::canvas::Canvas;
+use core::Colour;
+
+/// Compresses unbounded scene-referred light into the 0.0 to 1.0 range that
+/// eight and sixteen bit formats can hold.
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub enum ToneMapping {
+    /// Leaves values as they are, so the writer clips anything outside 0.0 to 1.0.
+    #[default]
+    None,
+    /// `c / (1 + c)` on each channel.
+    Reinhard,
+    /// Narkowicz's fit of the ACES reference rendering transform.
+    Aces,
+    /// Hable's filmic curve, with a linear white point of 11.2.
+    Filmic,
+}
+
+impl ToneMapping {
+    fn map(&self, value: f64) -> f64 {
+        // The operators are only defined for non-negative light.
+        let light = value.max(0.0);
+        match self {
+            ToneMapping::None => value,
+            ToneMapping::Reinhard => light / (1.0 + light),
+            ToneMapping::Aces => {
+                let mapped =
+                    (light * (2.51 * light + 0.03)) / (light * (2.43 * light + 0.59) + 0.14);
+                mapped.clamp(0.0, 1.0)
+            }
+            ToneMapping::Filmic => (hable(light) / hable(FILMIC_WHITE_POINT)).min(1.0),
+        }
+    }
+}
+
+const FILMIC_WHITE_POINT: f64 = 11.2;
+
+fn hable(x: f64) -> f64 {
+    let (a, b, c, d, e, f) = (0.15, 0.50, 0.10, 0.20, 0.02, 0.30);
+    (x * (a * x + c * b) + d * e) / (x * (a * x + b) + d * f) - e / f
+}
+
+/// How linear light is encoded into the values the writers store.
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub enum TransferFunction {
+    #[default]
+    Linear,
+    /// The piecewise sRGB curve, a linear toe followed by a 1/2.4 power.
+    Srgb,
+}
+
+impl TransferFunction {
+    fn encode(&self, value: f64) -> f64 {
+        match self {
+            TransferFunction::Linear => value,
+            TransferFunction::Srgb if value <= 0.0031308 => 12.92 * value.max(0.0),
+            TransferFunction::Srgb => 1.055 * value.powf(1.0 / 2.4) - 0.055,
+        }
+    }
+}
+
+/// Exposure, tone mapping and transfer encoding, applied in that order between
+/// rendering a `Canvas` and handing it to a writer. The default changes
+/// nothing, which suits formats such as HDR and EXR that keep raw light.
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub struct PostProcess {
+    /// Brightness adjustment in stops, so each +1.0 doubles the light.
+    pub exposure: f64,
+    pub tone_mapping: ToneMapping,
+    pub transfer: TransferFunction,
+}
+
+impl PostProcess {
+    /// ACES tone mapping and sRGB encoding, for viewing on an ordinary display.
+    pub fn display() -> PostProcess {
+        PostProcess {
+            tone_mapping: ToneMapping::Aces,
+            transfer: TransferFunction::Srgb,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_exposure(mut self, exposure: f64) -> Self {
+        self.exposure = exposure;
+        self
+    }
+
+    pub fn with_tone_mapping(mut self, tone_mapping: ToneMapping) -> Self {
+        self.tone_mapping = tone_mapping;
+        self
+    }
+
+    pub fn with_transfer(mut self, transfer: TransferFunction) -> Self {
+        self.transfer = transfer;
+        self
+    }
+
+    /// A processed copy of `canvas`, leaving the original free to be processed
+    /// differently for another output.
+    pub fn apply(&self, canvas: &Canvas) -> Canvas {
+        let mut processed = Canvas::new(canvas.width, canvas.height);
+        for y in 0..canvas.height {
+            for x in 0..canvas.width {
+                let colour = self.process(canvas.pixel_at(x, y).unwrap());
+                processed.write_pixel(x, y, colour).unwrap();
+            }
+        }
+        processed
+    }
+
+    pub fn process(&self, colour: &Colour) -> Colour {
+        let scale = 2f64.powf(self.exposure);
+        let [red, green, blue] = [colour.red(), colour.green(), colour.blue()]
+            .map(|value| self.transfer.encode(self.tone_mapping.map(value * scale)));
+        Colour::new(red, green, blue)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tone_mapping::{PostProcess, ToneMapping, TransferFunction};
+    use crate::Canvas;
+    use core::Colour;
+
+    fn approx(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-4
+    }
+
+    mod tone_mapping {
+        use super::*;
+
+        #[test]
+        fn none_keeps_values_outside_the_unit_range() {
+            assert_eq!(ToneMapping::None.map(4.0), 4.0);
+            assert_eq!(ToneMapping::None.map(-1.0), -1.0);
+            assert_eq!(ToneMapping::Reinhard.map(-1.0), 0.0);
+        }
+
+        #[test]
+        fn reinhard() {
+            assert_eq!(ToneMapping::Reinhard.map(0.0), 0.0);
+            assert_eq!(ToneMapping::Reinhard.map(1.0), 0.5);
+            assert_eq!(ToneMapping::Reinhard.map(3.0), 0.75);
+        }
+
+        #[test]
+        fn aces() {
+            assert!(approx(ToneMapping::Aces.map(0.0), 0.0));
+            assert!(approx(ToneMapping::Aces.map(0.18), 0.2669));
+            assert!(approx(ToneMapping::Aces.map(1.0), 0.8038));
+            assert_eq!(ToneMapping::Aces.map(100.0), 1.0);
+        }
+
+        #[test]
+        fn filmic_reaches_white_at_white_point() {
+            assert!(approx(ToneMapping::Filmic.map(0.0), 0.0));
+            assert!(approx(ToneMapping::Filmic.map(11.2), 1.0));
+            assert_eq!(ToneMapping::Filmic.map(50.0), 1.0);
+            assert!(ToneMapping::Filmic.map(1.0) < ToneMapping::Filmic.map(2.0));
+        }
+    }
+
+    mod transfer {
+        use super::*;
+
+        #[test]
+        fn srgb_encoding() {
+            let srgb = TransferFunction::Srgb;
+            assert_eq!(srgb.encode(0.0), 0.0);
+            assert!(approx(srgb.encode(0.002), 0.02584));
+            assert!(approx(srgb.encode(0.18), 0.4614));
+            assert!(approx(srgb.encode(1.0), 1.0));
+        }
+
+        #[test]
+        fn linear_is_unchanged() {
+            assert_eq!(TransferFunction::Linear.encode(0.18), 0.18);
+        }
+    }
+
+    mod post_process {
+        use super::*;
+
+        #[test]
+        fn default_changes_nothing() {
+            let colour = Colour::new(2.5, 0.5, 0.0);
+            assert_eq!(PostProcess::default().process(&colour), colour);
+        }
+
+        #[test]
+        fn exposure_is_in_stops() {
+            let post_process = PostProcess::default().with_exposure(2.0);
+            assert_eq!(
+                post_process.process(&Colour::new(0.25, 0.5, 1.0)),
+                Colour::new(1.0, 2.0, 4.0)
+            );
+        }
+
+        #[test]
+        fn exposure_then_tone_mapping_then_transfer() {
+            let post_process = PostProcess::default()
+                .with_exposure(-1.0)
+                .with_tone_mapping(ToneMapping::Reinhard)
+                .with_transfer(TransferFunction::Srgb);
+
+            let colour = post_process.process(&Colour::new(2.0, 0.0, 6.0));
+
+            assert!(approx(colour.red(), 0.7354));
+            assert_eq!(colour.green(), 0.0);
+            assert!(approx(colour.blue(), TransferFunction::Srgb.encode(0.75)));
+        }
+
+        #[test]
+        fn applying_leaves_the_original_canvas() {
+            let mut canvas = Canvas::new(2, 1);
+            canvas
+                .write_pixel(1, 0, Colour::new(8.0, 1.0, 0.0))
+                .unwrap();
+
+            let processed = PostProcess::display().apply(&canvas);
+
+            assert_eq!(*canvas.pixel_at(1, 0).unwrap(), Colour::new(8.0, 1.0, 0.0));
+            let pixel = processed.pixel_at(1, 0).unwrap();
+            assert!(pixel.red() <= 1.0 && pixel.red() > pixel.green());
+            assert_eq!(
+                *processed.pixel_at(0, 0).unwrap(),
+                Colour::new(0.0, 0.0, 0.0)
+            );
+        }
+    }
+}
@@ -0,0 +1,573 @@
+//! Just enough of zlib (RFC 1950) and DEFLATE (RFC 1951) to read and write
+//! compressed image data, plus the CRC-32 used to checksum PNG chunks.
+
+use crate::read_error::ImageReadError;
+
+const WINDOW_SIZE: usize = 32 * 1024;
+const MIN_MATCH: usize = 3;
+const MAX_MATCH: usize = 258;
+const MAX_CHAIN: usize = 64;
+const HASH_BITS: usize = 15;
+
+const CODE_LENGTH_ORDER: [usize; 19] = [
+    16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15,
+];
+const MAX_CODE_LENGTH: usize = 15;
+
+const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+const LENGTH_EXTRA: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+const DISTANCE_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DISTANCE_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+
+pub fn crc32(bytes: &[u8]) -> u32 {
+    crc32_update(0, bytes)
+}
+
+/// Continues a CRC-32 over `bytes`, so that a checksum can span several slices.
+pub fn crc32_update(crc: u32, bytes: &[u8]) -> u32 {
+    let mut crc = !crc;
+    for byte in bytes {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xEDB8_8320 & mask);
+        }
+    }
+    !crc
+}
+
+pub fn adler32(bytes: &[u8]) -> u32 {
+    const MODULUS: u32 = 65521;
+
+    let (mut a, mut b) = (1u32, 0u32);
+    for chunk in bytes.chunks(5552) {
+        for byte in chunk {
+            a += *byte as u32;
+            b += a;
+        }
+        a %= MODULUS;
+        b %= MODULUS;
+    }
+    (b << 16) | a
+}
+
+/// Wraps a DEFLATE stream of `bytes` in a zlib header and Adler-32 trailer.
+pub fn compress(bytes: &[u8]) -> Vec<u8> {
+    let mut output = vec![0x78, 0x9C];
+    output.extend(deflate(bytes));
+    output.extend(adler32(bytes).to_be_bytes());
+    output
+}
+
+/// Compresses `bytes` as a single DEFLATE block using the fixed Huffman codes and
+/// greedy LZ77 matching over a hash chain.
+pub fn deflate(bytes: &[u8]) -> Vec<u8> {
+    let mut writer = BitWriter::default();
+    writer.write_bits(1, 1);
+    writer.write_bits(1, 2);
+
+    let mut head = vec![usize::MAX; 1 << HASH_BITS];
+    let mut previous = vec![usize::MAX; WINDOW_SIZE];
+
+    let mut position = 0;
+    while position < bytes.len() {
+        let (length, distance) = longest_match(bytes, position, &head, &previous);
+
+        let advance = if length >= MIN_MATCH {
+            write_length(&mut writer, length);
+            write_distance(&mut writer, distance);
+            length
+        } else {
+            write_literal(&mut writer, bytes[position] as u16);
+            1
+        };
+
+        for p in position..position + advance {
+            if p + MIN_MATCH <= bytes.len() {
+                let hash = hash(&bytes[p..]);
+                previous[p % WINDOW_SIZE] = head[hash];
+                head[hash] = p;
+            }
+        }
+        position += advance;
+    }
+
+    write_literal(&mut writer, 256);
+    writer.finish()
+}
+
+fn hash(bytes: &[u8]) -> usize {
+    let value = (bytes[0] as usize) << 16 | (bytes[1] as usize) << 8 | bytes[2] as usize;
+    (value.wrapping_mul(2_654_435_761) >> 8) & ((1 << HASH_BITS) - 1)
+}
+
+fn longest_match(
+    bytes: &[u8],
+    position: usize,
+    head: &[usize],
+    previous: &[usize],
+) -> (usize, usize) {
+    if position + MIN_MATCH > bytes.len() {
+        return (0, 0);
+    }
+
+    let limit = (bytes.len() - position).min(MAX_MATCH);
+    let (mut best_length, mut best_distance) = (0, 0);
+    let mut candidate = head[hash(&bytes[position..])];
+
+    for _ in 0..MAX_CHAIN {
+        if candidate == usize::MAX || position - candidate > WINDOW_SIZE {
+            break;
+        }
+
+        let length = bytes[candidate..]
+            .iter()
+            .zip(&bytes[position..position + limit])
+            .take_while(|(a, b)| a == b)
+            .count();
+        if length > best_length {
+            best_length = length;
+            best_distance = position - candidate;
+            if length == limit {
+                break;
+            }
+        }
+
+        let next = previous[candidate % WINDOW_SIZE];
+        if next == usize::MAX || next >= candidate {
+            break;
+        }
+        candidate = next;
+    }
+
+    (best_length, best_distance)
+}
+
+fn write_literal(writer: &mut BitWriter, symbol: u16) {
+    let (code, length) = match symbol {
+        0..=143 => (0x30 + symbol, 8),
+        144..=255 => (0x190 + symbol - 144, 9),
+        256..=279 => (symbol - 256, 7),
+        _ => (0xC0 + symbol - 280, 8),
+    };
+    writer.write_huffman(code, length);
+}
+
+fn write_length(writer: &mut BitWriter, length: usize) {
+    let index = LENGTH_BASE
+        .iter()
+        .rposition(|base| *base as usize <= length)
+        .expect("match lengths are at least 3");
+    write_literal(writer, 257 + index as u16);
+    writer.write_bits(
+        (length - LENGTH_BASE[index] as usize) as u32,
+        LENGTH_EXTRA[index],
+    );
+}
+
+fn write_distance(writer: &mut BitWriter, distance: usize) {
+    let index = DISTANCE_BASE
+        .iter()
+        .rposition(|base| *base as usize <= distance)
+        .expect("match distances are at least 1");
+    writer.write_huffman(index as u16, 5);
+    writer.write_bits(
+        (distance - DISTANCE_BASE[index] as usize) as u32,
+        DISTANCE_EXTRA[index],
+    );
+}
+
+#[derive(Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    buffer: u64,
+    count: u8,
+}
+
+impl BitWriter {
+    /// Writes the low `count` bits of `value`, least significant bit first.
+    fn write_bits(&mut self, value: u32, count: u8) {
+        self.buffer |= (value as u64) << self.count;
+        self.count += count;
+        while self.count >= 8 {
+            self.bytes.push(self.buffer as u8);
+            self.buffer >>= 8;
+            self.count -= 8;
+        }
+    }
+
+    /// Huffman codes are packed starting from their most significant bit.
+    fn write_huffman(&mut self, code: u16, length: u8) {
+        let reversed = (code.reverse_bits() >> (16 - length)) as u32;
+        self.write_bits(reversed, length);
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.count > 0 {
+            self.bytes.push(self.buffer as u8);
+        }
+        self.bytes
+    }
+}
+
+/// Checks the zlib header and Adler-32 trailer around an inflated DEFLATE stream.
+pub fn decompress(bytes: &[u8]) -> Result<Vec<u8>, ImageReadError> {
+    if bytes.len() < 6 {
+        return Err(ImageReadError::Truncated);
+    }
+
+    let (cmf, flg) = (bytes[0], bytes[1]);
+    if cmf & 0x0F != 8 || !(cmf as u16 * 256 + flg as u16).is_multiple_of(31) {
+        return Err(ImageReadError::Malformed("invalid zlib header".to_string()));
+    }
+    if flg & 0x20 != 0 {
+        return Err(ImageReadError::Unsupported(
+            "zlib preset dictionaries".to_string(),
+        ));
+    }
+
+    let mut reader = BitReader::new(&bytes[2..]);
+    let output = inflate(&mut reader)?;
+
+    let trailer = reader.aligned_bytes(4)?;
+    let expected = u32::from_be_bytes([trailer[0], trailer[1], trailer[2], trailer[3]]);
+    if adler32(&output) != expected {
+        return Err(ImageReadError::Malformed(
+            "zlib checksum mismatch".to_string(),
+        ));
+    }
+
+    Ok(output)
+}
+
+fn inflate(reader: &mut BitReader) -> Result<Vec<u8>, ImageReadError> {
+    let mut output = Vec::new();
+
+    loop {
+        let last = reader.bits(1)? == 1;
+        match reader.bits(2)? {
+            0 => inflate_stored(reader, &mut output)?,
+            1 => {
+                let (literals, distances) = fixed_codes();
+                inflate_codes(reader, &mut output, &literals, &distances)?;
+            }
+            2 => {
+                let (literals, distances) = dynamic_codes(reader)?;
+                inflate_codes(reader, &mut output, &literals, &distances)?;
+            }
+            _ => {
+                return Err(ImageReadError::Malformed(
+                    "invalid DEFLATE block type".to_string(),
+                ))
+            }
+        }
+
+        if last {
+            return Ok(output);
+        }
+    }
+}
+
+fn inflate_stored(reader: &mut BitReader, output: &mut Vec<u8>) -> Result<(), ImageReadError> {
+    let header = reader.aligned_bytes(4)?;
+    let length = u16::from_le_bytes([header[0], header[1]]);
+    let complement = u16::from_le_bytes([header[2], header[3]]);
+    if length != !complement {
+        return Err(ImageReadError::Malformed(
+            "stored block length mismatch".to_string(),
+        ));
+    }
+
+    output.extend(reader.aligned_bytes(length as usize)?);
+    Ok(())
+}
+
+fn inflate_codes(
+    reader: &mut BitReader,
+    output: &mut Vec<u8>,
+    literals: &Huffman,
+    distances: &Huffman,
+) -> Result<(), ImageReadError> {
+    loop {
+        let symbol = literals.decode(reader)? as usize;
+        match symbol {
+            0..=255 => output.push(symbol as u8),
+            256 => return Ok(()),
+            257..=285 => {
+                let index = symbol - 257;
+                let length =
+                    LENGTH_BASE[index] as usize + reader.bits(LENGTH_EXTRA[index])? as usize;
+
+                let index = distances.decode(reader)? as usize;
+                if index >= DISTANCE_BASE.len() {
+                    return Err(ImageReadError::Malformed(
+                        "invalid DEFLATE distance code".to_string(),
+                    ));
+                }
+                let distance =
+                    DISTANCE_BASE[index] as usize + reader.bits(DISTANCE_EXTRA[index])? as usize;
+                if distance > output.len() {
+                    return Err(ImageReadError::Malformed(
+                        "DEFLATE distance is too far back".to_string(),
+                    ));
+                }
+
+                let start = output.len() - distance;
+                for i in 0..length {
+                    output.push(output[start + i]);
+                }
+            }
+            _ => {
+                return Err(ImageReadError::Malformed(
+                    "invalid DEFLATE length code".to_string(),
+                ))
+            }
+        }
+    }
+}
+
+fn fixed_codes() -> (Huffman, Huffman) {
+    let mut lengths = [8; 288];
+    lengths[144..256].fill(9);
+    lengths[256..280].fill(7);
+
+    (Huffman::new(&lengths), Huffman::new(&[5; 30]))
+}
+
+fn dynamic_codes(reader: &mut BitReader) -> Result<(Huffman, Huffman), ImageReadError> {
+    let literal_count = reader.bits(5)? as usize + 257;
+    let distance_count = reader.bits(5)? as usize + 1;
+    let code_length_count = reader.bits(4)? as usize + 4;
+
+    let mut code_lengths = [0; 19];
+    for position in &CODE_LENGTH_ORDER[..code_length_count] {
+        code_lengths[*position] = reader.bits(3)? as u8;
+    }
+    let code_lengths = Huffman::new(&code_lengths);
+
+    let mut lengths = Vec::with_capacity(literal_count + distance_count);
+    while lengths.len() < literal_count + distance_count {
+        let (value, repeat) = match code_lengths.decode(reader)? {
+            symbol @ 0..=15 => (symbol as u8, 1),
+            16 => {
+                let previous = *lengths.last().ok_or_else(|| {
+                    ImageReadError::Malformed("repeated code length with no previous".to_string())
+                })?;
+                (previous, 3 + reader.bits(2)?)
+            }
+            17 => (0, 3 + reader.bits(3)?),
+            _ => (0, 11 + reader.bits(7)?),
+        };
+        lengths.extend(std::iter::repeat_n(value, repeat as usize));
+    }
+
+    if lengths.len() > literal_count + distance_count {
+        return Err(ImageReadError::Malformed(
+            "code lengths overrun their table".to_string(),
+        ));
+    }
+
+    let (literals, distances) = lengths.split_at(literal_count);
+    Ok((Huffman::new(literals), Huffman::new(distances)))
+}
+
+/// A canonical Huffman code, stored as the number of codes of each length and the
+/// symbols in code order.
+struct Huffman {
+    counts: [u16; MAX_CODE_LENGTH + 1],
+    symbols: Vec<u16>,
+}
+
+impl Huffman {
+    fn new(lengths: &[u8]) -> Self {
+        let mut counts = [0; MAX_CODE_LENGTH + 1];
+        for length in lengths {
+            counts[*length as usize] += 1;
+        }
+        counts[0] = 0;
+
+        let mut offsets = [0; MAX_CODE_LENGTH + 1];
+        for length in 1..MAX_CODE_LENGTH {
+            offsets[length + 1] = offsets[length] + counts[length];
+        }
+
+        let mut symbols = vec![0; lengths.iter().filter(|length| **length > 0).count()];
+        for (symbol, length) in lengths.iter().enumerate() {
+            if *length > 0 {
+                symbols[offsets[*length as usize] as usize] = symbol as u16;
+                offsets[*length as usize] += 1;
+            }
+        }
+
+        Self { counts, symbols }
+    }
+
+    fn decode(&self, reader: &mut BitReader) -> Result<u16, ImageReadError> {
+        let (mut code, mut first, mut index) = (0i32, 0i32, 0i32);
+
+        for length in 1..=MAX_CODE_LENGTH {
+            code |= reader.bits(1)? as i32;
+            let count = self.counts[length] as i32;
+            if code - first < count {
+                return Ok(self.symbols[(index + code - first) as usize]);
+            }
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+
+        Err(ImageReadError::Malformed(
+            "invalid Huffman code".to_string(),
+        ))
+    }
+}
+
+struct BitReader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+    buffer: u32,
+    count: u8,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Self {
+            bytes,
+            position: 0,
+            buffer: 0,
+            count: 0,
+        }
+    }
+
+    /// Reads `count` bits, least significant bit first.
+    fn bits(&mut self, count: u8) -> Result<u32, ImageReadError> {
+        while self.count < count {
+            let byte = *self
+                .bytes
+                .get(self.position)
+                .ok_or(ImageReadError::Truncated)?;
+            self.buffer |= (byte as u32) << self.count;
+            self.position += 1;
+            self.count += 8;
+        }
+
+        let value = self.buffer & ((1u64 << count) - 1) as u32;
+        self.buffer >>= count;
+        self.count -= count;
+        Ok(value)
+    }
+
+    /// Discards any partial byte, then reads `count` whole bytes.
+    fn aligned_bytes(&mut self, count: usize) -> Result<&'a [u8], ImageReadError> {
+        self.buffer = 0;
+        self.count = 0;
+
+        let bytes = self
+            .bytes
+            .get(self.position..self.position + count)
+            .ok_or(ImageReadError::Truncated)?;
+        self.position += count;
+        Ok(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn crc32_check_value() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+        assert_eq!(crc32_update(crc32(b"1234"), b"56789"), 0xCBF4_3926);
+        assert_eq!(crc32(b""), 0);
+    }
+
+    #[test]
+    fn adler32_check_value() {
+        assert_eq!(adler32(b"Wikipedia"), 0x11E6_0398);
+        assert_eq!(adler32(b""), 1);
+    }
+
+    #[test]
+    fn empty_input() {
+        assert_eq!(deflate(b""), vec![0x03, 0x00]);
+    }
+
+    #[test]
+    fn single_literal() {
+        assert_eq!(
+            compress(b"a"),
+            vec![0x78, 0x9C, 0x4B, 0x04, 0x00, 0x00, 0x62, 0x00, 0x62]
+        );
+    }
+
+    #[test]
+    fn repeated_input_is_compressed() {
+        let input = vec![7u8; 10_000];
+        assert!(deflate(&input).len() < 100);
+    }
+
+    #[test]
+    fn round_trip() {
+        let input: Vec<u8> = (0..50_000u32)
+            .map(|i| (((i * 31) % 251) ^ (i / 97)) as u8)
+            .collect();
+        assert_eq!(decompress(&compress(&input)).unwrap(), input);
+        assert_eq!(decompress(&compress(b"")).unwrap(), b"");
+    }
+
+    #[test]
+    fn decompress_stored_block() {
+        let stream = [
+            0x78, 0x01, 0x01, 0x0C, 0x00, 0xF3, 0xFF, 0x73, 0x74, 0x6F, 0x72, 0x65, 0x64, 0x20,
+            0x62, 0x6C, 0x6F, 0x63, 0x6B, 0x1F, 0x80, 0x04, 0xBD,
+        ];
+        assert_eq!(decompress(&stream).unwrap(), b"stored block");
+    }
+
+    #[test]
+    fn decompress_dynamic_block() {
+        let stream = [
+            0x78, 0xDA, 0x35, 0x8C, 0x81, 0x0D, 0xC0, 0x40, 0x08, 0x02, 0x67, 0x3D, 0x60, 0xFF,
+            0x19, 0x8A, 0x7C, 0xAA, 0x46, 0x05, 0x11, 0x24, 0x88, 0xE8, 0x10, 0x31, 0xED, 0x2D,
+            0xF9, 0xC6, 0x90, 0xEF, 0xB8, 0xFD, 0x38, 0xE3, 0x63, 0x95, 0x94, 0x0A, 0x13, 0xA6,
+            0xF9, 0xA2, 0xEA, 0x3D, 0x99, 0xDF, 0x90, 0x53, 0x16, 0x6A, 0x16, 0x8A, 0xDE, 0xC1,
+            0xFA, 0x00, 0xD8, 0xBB, 0x2D, 0xE7,
+        ];
+        let expected = "abbaadbabbabadcaabaababcbaabcaabacdbababcaacbaacaccaabbddabcdaabcbadadaaa\
+                        aaaabacbcaabcababbabadabddacabbbabcabdbbabbabcb";
+        assert_eq!(decompress(&stream).unwrap(), expected.as_bytes());
+    }
+
+    #[test]
+    fn decompress_rejects_bad_streams() {
+        let stream = compress(b"checksummed");
+
+        let mut corrupted = stream.clone();
+        let last = corrupted.len() - 1;
+        corrupted[last] ^= 1;
+        assert!(matches!(
+            decompress(&corrupted),
+            Err(ImageReadError::Malformed(_))
+        ));
+        assert!(matches!(
+            decompress(&stream[..stream.len() - 2]),
+            Err(ImageReadError::Truncated)
+        ));
+        assert!(matches!(
+            decompress(&[0x78, 0x9D, 0, 0, 0, 0]),
+            Err(ImageReadError::Malformed(_))
+        ));
+    }
+}
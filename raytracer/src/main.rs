@@ -1,11 +1,72 @@
-use output::{write_ppm, PpmWrapper};
+use output::{
+    write_image, write_ppm_streamed, PostProcess, PpmFormat, PpmWrapper, ToneMapping,
+    TransferFunction,
+};
 use raytracer::pattern;
 use std::f64::consts::PI;
 
+const USAGE: &str =
+    "usage: raytracer [--exposure STOPS] [--tone-map none|reinhard|aces|filmic] [--srgb] [OUTPUT]";
+
+/// Splits the command line into an optional output path and the post-process
+/// its flags describe. Without flags the image is written as rendered.
+fn parse_args(
+    mut args: impl Iterator<Item = String>,
+) -> Result<(Option<String>, PostProcess), String> {
+    let mut filename = None;
+    let mut post_process = PostProcess::default();
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--exposure" => {
+                let stops = args.next().ok_or("--exposure needs a value")?;
+                let stops = stops
+                    .parse()
+                    .map_err(|_| format!("invalid exposure {}", stops))?;
+                post_process = post_process.with_exposure(stops);
+            }
+            "--tone-map" => {
+                let tone_mapping = match args.next().as_deref() {
+                    Some("none") => ToneMapping::None,
+                    Some("reinhard") => ToneMapping::Reinhard,
+                    Some("aces") => ToneMapping::Aces,
+                    Some("filmic") => ToneMapping::Filmic,
+                    Some(other) => return Err(format!("unknown tone mapping {}", other)),
+                    None => return Err("--tone-map needs a value".to_string()),
+                };
+                post_process = post_process.with_tone_mapping(tone_mapping);
+            }
+            "--srgb" => post_process = post_process.with_transfer(TransferFunction::Srgb),
+            _ if arg.starts_with("--") => return Err(format!("unknown option {}", arg)),
+            _ if filename.is_none() => filename = Some(arg),
+            _ => return Err(format!("unexpected argument {}", arg)),
+        }
+    }
+
+    Ok((filename, post_process))
+}
+
 fn main() {
+    let (filename, post_process) = match parse_args(std::env::args().skip(1)) {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            eprintln!("{}\n{}", e, USAGE);
+            std::process::exit(2);
+        }
+    };
+
     let canvas = pattern(4096, 2160, PI / 1.5);
-    let ppm_wrapper = PpmWrapper::new(canvas, 255);
-    if let Err(e) = write_ppm::<std::fs::File>(&ppm_wrapper) {
-        eprintln!("Failed to write PPM file: {}", e);
+
+    // An optional output path picks the image format from its extension. HDR and
+    // EXR keep the raw light whatever post-process the flags ask for.
+    let result = match filename {
+        Some(filename) => write_image::<std::fs::File>(canvas, &filename, &post_process),
+        None => write_ppm_streamed::<std::fs::File>(
+            &PpmWrapper::new(post_process.apply(&canvas), 255),
+            PpmFormat::Binary,
+        ),
+    };
+    if let Err(e) = result {
+        eprintln!("Failed to write image file: {}", e);
     }
 }
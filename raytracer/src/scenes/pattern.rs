@@ -4,7 +4,7 @@ use output::Canvas;
 use render::{Camera, PointLight, World};
 use shapes::{Material, Patn, Pattern, Plane, Sphere};
 
-pub fn pattern(h_res: usize, v_res: usize, focal_length: f64) -> Canvas {
+pub fn pattern(h_res: usize, v_res: usize, field_of_view: f64) -> Canvas {
     let mut fm = Material::new(Pattern::new(
         Patn::Checkers(Colour::new(1.0, 1.0, 1.0), Colour::new(0.5, 0.5, 0.5)),
         Transform::default().build(),
@@ -106,12 +106,12 @@ pub fn pattern(h_res: usize, v_res: usize, focal_length: f64) -> Canvas {
     );
 
     let light = PointLight::new(Point::new(-7.0, 10.0, -10.0), Colour::new(1.0, 1.0, 1.0));
-    let world = World::new(vec![&floor, &wall, &s1, &s2, &s3, &s4], light);
+    let world = World::new(vec![&floor, &wall, &s1, &s2, &s3, &s4], vec![light]);
 
     let camera = Camera::new(
         h_res,
         v_res,
-        focal_length,
+        field_of_view,
         Transform::default()
             .view_transform(
                 Point::new(-1.0, 2.0, -9.0),
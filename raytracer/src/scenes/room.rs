@@ -5,7 +5,7 @@ use render::{Camera, PointLight, World};
 use shapes::{Material, Patn, Pattern, Plane, Sphere};
 use std::f64::consts::PI;
 
-pub fn room(h_res: usize, v_res: usize, focal_length: f64) -> Canvas {
+pub fn room(h_res: usize, v_res: usize, field_of_view: f64) -> Canvas {
     let mut material1 = Material::new(Pattern::new(
         Patn::Solid(Colour::new(1.0, 0.0, 0.0)),
         Matrix4::identity(),
@@ -130,13 +130,13 @@ pub fn room(h_res: usize, v_res: usize, focal_length: f64) -> Canvas {
             &right_wall,
             &left_wall,
         ],
-        light,
+        vec![light],
     );
 
     let camera = Camera::new(
         h_res,
         v_res,
-        focal_length,
+        field_of_view,
         Transform::default()
             .view_transform(
                 Point::new(0.0, 0.0, -12.0),
@@ -4,7 +4,7 @@ use output::Canvas;
 use render::{Camera, PointLight, World};
 use shapes::{Material, Patn, Pattern, Plane, Sphere};
 
-pub fn simple_plane(h_res: usize, v_res: usize, focal_length: f64) -> Canvas {
+pub fn simple_plane(h_res: usize, v_res: usize, field_of_view: f64) -> Canvas {
     let mut fm = Material::new(Pattern::new(
         Patn::Solid(Colour::new(1.0, 1.0, 1.0)),
         Matrix4::identity(),
@@ -50,12 +50,12 @@ pub fn simple_plane(h_res: usize, v_res: usize, focal_length: f64) -> Canvas {
     );
 
     let light = PointLight::new(Point::new(-10.0, 10.0, -10.0), Colour::new(1.0, 1.0, 1.0));
-    let world = World::new(vec![&floor, &middle, &left, &right], light);
+    let world = World::new(vec![&floor, &middle, &left, &right], vec![light]);
 
     let camera = Camera::new(
         h_res,
         v_res,
-        focal_length,
+        field_of_view,
         Transform::default()
             .view_transform(
                 Point::new(0.0, 1.5, -5.0),
@@ -0,0 +1,205 @@
+use core::Point;
+use math::Ray;
+use shapes::{BoundingBox, Intersection, Shape};
+
+const MAX_LEAF_SIZE: usize = 4;
+
+enum Node<'a> {
+    Leaf(BoundingBox, Vec<&'a Shape>),
+    Branch(BoundingBox, Box<Node<'a>>, Box<Node<'a>>),
+}
+
+pub(crate) struct Bvh<'a> {
+    root: Option<Node<'a>>,
+    unbounded: Vec<&'a Shape>,
+}
+
+impl<'a> Bvh<'a> {
+    pub(crate) fn new(shapes: &[&'a Shape]) -> Self {
+        let mut primitives = vec![];
+        for shape in shapes {
+            collect_primitives(shape, &mut primitives);
+        }
+
+        let (bounded, unbounded): (Vec<_>, Vec<_>) = primitives
+            .into_iter()
+            .map(|shape| (shape, shape.bounds()))
+            .partition(|(_, bounds)| bounds.is_finite());
+
+        Self {
+            root: (!bounded.is_empty()).then(|| build(bounded)),
+            unbounded: unbounded.into_iter().map(|(shape, _)| shape).collect(),
+        }
+    }
+
+    pub(crate) fn intersect(&self, ray: &Ray) -> Vec<Intersection<'a>> {
+        let mut intersections: Vec<_> = self
+            .unbounded
+            .iter()
+            .flat_map(|shape| shape.intersect(ray))
+            .collect();
+
+        if let Some(root) = &self.root {
+            root.intersect(ray, &mut intersections);
+        }
+
+        intersections
+    }
+}
+
+impl<'a> Node<'a> {
+    fn bounds(&self) -> &BoundingBox {
+        match self {
+            Node::Leaf(bounds, _) | Node::Branch(bounds, _, _) => bounds,
+        }
+    }
+
+    fn intersect(&self, ray: &Ray, intersections: &mut Vec<Intersection<'a>>) {
+        if !self.bounds().intersects(ray) {
+            return;
+        }
+
+        match self {
+            Node::Leaf(_, shapes) => {
+                intersections.extend(shapes.iter().flat_map(|shape| shape.intersect(ray)))
+            }
+            Node::Branch(_, left, right) => {
+                left.intersect(ray, intersections);
+                right.intersect(ray, intersections);
+            }
+        }
+    }
+}
+
+// Groups only exist to share a transform, which their children already carry, so their
+// contents are flattened into the hierarchy. CSG shapes stay whole because their
+// intersections have to be filtered together.
+fn collect_primitives<'a>(shape: &'a Shape, primitives: &mut Vec<&'a Shape>) {
+    match shape {
+        Shape::Group(group) => {
+            for child in &group.children {
+                collect_primitives(child, primitives);
+            }
+        }
+        _ => primitives.push(shape),
+    }
+}
+
+fn build(mut primitives: Vec<(&Shape, BoundingBox)>) -> Node<'_> {
+    let bounds = primitives
+        .iter()
+        .fold(BoundingBox::empty(), |bounds, (_, b)| bounds.merge(b));
+
+    let centroids = primitives
+        .iter()
+        .fold(BoundingBox::empty(), |centroids, (_, b)| {
+            centroids.add_point(&b.centroid())
+        });
+    let extent = &centroids.max - &centroids.min;
+    let axis = if extent.x() >= extent.y() && extent.x() >= extent.z() {
+        0
+    } else if extent.y() >= extent.z() {
+        1
+    } else {
+        2
+    };
+
+    if primitives.len() <= MAX_LEAF_SIZE || extent.magnitude() == 0.0 {
+        return Node::Leaf(bounds, primitives.into_iter().map(|(s, _)| s).collect());
+    }
+
+    primitives.sort_by(|(_, a), (_, b)| {
+        axis_value(&a.centroid(), axis).total_cmp(&axis_value(&b.centroid(), axis))
+    });
+    let right = primitives.split_off(primitives.len() / 2);
+
+    Node::Branch(bounds, Box::new(build(primitives)), Box::new(build(right)))
+}
+
+fn axis_value(point: &Point, axis: usize) -> f64 {
+    match axis {
+        0 => point.x(),
+        1 => point.y(),
+        _ => point.z(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bvh::Bvh;
+    use core::{Point, Vector};
+    use math::{Ray, Transform};
+    use shapes::{Group, Material, Plane, Shape, Sphere};
+
+    fn spheres() -> Vec<Shape> {
+        (0..20)
+            .map(|i| {
+                Sphere::new(
+                    Transform::default()
+                        .scaling(0.4, 0.4, 0.4)
+                        .translation((i % 5) as f64, (i / 5) as f64, 0.0)
+                        .build(),
+                    Material::default(),
+                )
+            })
+            .collect()
+    }
+
+    fn sorted_ts(bvh: &Bvh, ray: &Ray) -> Vec<f64> {
+        let mut ts: Vec<_> = bvh.intersect(ray).iter().map(|i| i.t).collect();
+        ts.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        ts
+    }
+
+    #[test]
+    fn matches_brute_force() {
+        let shapes = spheres();
+        let refs: Vec<&Shape> = shapes.iter().collect();
+        let bvh = Bvh::new(&refs);
+
+        let rays = [
+            Ray::new(Point::new(2.0, 1.0, -5.0), Vector::new(0.0, 0.0, 1.0)),
+            Ray::new(Point::new(-5.0, 2.0, 0.0), Vector::new(1.0, 0.0, 0.0)),
+            Ray::new(
+                Point::new(-1.0, -1.0, -1.0),
+                Vector::new(1.0, 1.0, 0.2).normalize(),
+            ),
+            Ray::new(Point::new(10.0, 10.0, -5.0), Vector::new(0.0, 0.0, 1.0)),
+        ];
+
+        for ray in rays {
+            let mut expected: Vec<_> = refs
+                .iter()
+                .flat_map(|shape| shape.intersect(&ray))
+                .map(|i| i.t)
+                .collect();
+            expected.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+            assert_eq!(sorted_ts(&bvh, &ray), expected);
+        }
+    }
+
+    #[test]
+    fn unbounded_shapes_are_always_tested() {
+        let plane = Plane::new(Transform::default().build(), Material::default());
+        let shapes = spheres();
+        let mut refs: Vec<&Shape> = shapes.iter().collect();
+        refs.push(&plane);
+        let bvh = Bvh::new(&refs);
+
+        let ray = Ray::new(Point::new(50.0, 5.0, 50.0), Vector::new(0.0, -1.0, 0.0));
+        assert_eq!(sorted_ts(&bvh, &ray), vec![5.0]);
+    }
+
+    #[test]
+    fn groups_are_flattened() {
+        let group = Group::new(Transform::default().build(), spheres());
+        let bvh = Bvh::new(&[&group]);
+
+        let ray = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+        let xs = bvh.intersect(&ray);
+
+        assert_eq!(xs.len(), 2);
+        assert!(matches!(xs[0].shape, Shape::Sphere(_)));
+    }
+}
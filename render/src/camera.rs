@@ -1,18 +1,105 @@
 use crate::World;
+use core::Colour;
 use core::Point;
 use math::{Matrix4, Ray};
 use output::Canvas;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 use rayon::prelude::*;
+use std::f64::consts::PI;
 
+/// Where within each pixel the camera fires its rays.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Sampling {
+    /// An `n` by `n` grid of rays through the centres of equal sub-pixels.
+    Grid(usize),
+    /// An `n` by `n` grid of sub-pixels, each sampled at a random point inside it.
+    Jittered(usize),
+    /// `n` rays at uniformly random points anywhere in the pixel.
+    Random(usize),
+    /// One ray per pixel, after which pixels differing from a neighbour by more
+    /// than `threshold` in any channel are recursively split into quadrants, up to
+    /// `max_depth` times.
+    Adaptive { threshold: f64, max_depth: usize },
+}
+
+/// How the camera maps canvas positions to rays.
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub enum Projection {
+    /// A pinhole, or thin lens with depth of field, covering the field of view.
+    #[default]
+    Perspective,
+    /// Parallel rays covering `view_width` units horizontally, with no
+    /// perspective distortion. Depth of field does not apply.
+    Orthographic { view_width: f64 },
+    /// A full 360° by 180° panorama, suitable for environment maps.
+    Equirectangular,
+    /// A circular fisheye image covering `field_of_view` radians across, such as
+    /// PI for a hemisphere. Pixels outside the circle are left black.
+    Fisheye { field_of_view: f64 },
+}
+
+/// How many rays a render fired from the camera.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct RenderReport {
+    pub primary_rays: usize,
+    /// Rays spent refining high-contrast pixels in adaptive sampling.
+    pub extra_rays: usize,
+}
+
+impl Default for Sampling {
+    fn default() -> Self {
+        Sampling::Grid(1)
+    }
+}
+
+impl Sampling {
+    pub fn samples_per_pixel(&self) -> usize {
+        match self {
+            Sampling::Grid(n) | Sampling::Jittered(n) => (*n).max(1).pow(2),
+            Sampling::Random(n) => (*n).max(1),
+            Sampling::Adaptive { .. } => 1,
+        }
+    }
+
+    /// Offsets of each sample from the pixel's top-left corner, in the range [0, 1).
+    fn offsets(&self, rng: &mut impl Rng) -> Vec<(f64, f64)> {
+        match *self {
+            Sampling::Grid(n) => Sampling::stratified(n, || (0.5, 0.5)),
+            Sampling::Jittered(n) => Sampling::stratified(n, || (rng.gen(), rng.gen())),
+            Sampling::Random(n) => (0..n.max(1)).map(|_| (rng.gen(), rng.gen())).collect(),
+            Sampling::Adaptive { .. } => vec![(0.5, 0.5)],
+        }
+    }
+
+    fn stratified(n: usize, mut jitter: impl FnMut() -> (f64, f64)) -> Vec<(f64, f64)> {
+        let n = n.max(1);
+        let cell = 1.0 / n as f64;
+
+        (0..n * n)
+            .map(|i| {
+                let (jx, jy) = jitter();
+                (((i % n) as f64 + jx) * cell, ((i / n) as f64 + jy) * cell)
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct Camera {
     hsize: usize,
     vsize: usize,
     #[allow(dead_code)]
     field_of_view: f64,
     transform: Matrix4,
+    inverse_transform: Matrix4,
     half_width: f64,
     half_height: f64,
     pixel_size: f64,
+    sampling: Sampling,
+    projection: Projection,
+    aperture: f64,
+    focal_distance: f64,
 }
 
 impl Camera {
@@ -32,53 +119,307 @@ impl Camera {
             hsize,
             vsize,
             field_of_view,
+            inverse_transform: transform.inverse(),
             transform,
             half_width,
             half_height,
             pixel_size,
+            sampling: Sampling::default(),
+            projection: Projection::default(),
+            aperture: 0.0,
+            focal_distance: 1.0,
         }
     }
 
+    pub fn with_projection(mut self, projection: Projection) -> Self {
+        self.projection = projection;
+        self
+    }
+
+    /// Models a thin lens `aperture` units across, so that only objects
+    /// `focal_distance` units in front of the camera are in sharp focus. Each ray
+    /// starts from a random point on the lens, so pair this with supersampling.
+    pub fn with_depth_of_field(mut self, aperture: f64, focal_distance: f64) -> Self {
+        self.aperture = aperture;
+        self.focal_distance = focal_distance;
+        self
+    }
+
+    pub(crate) fn transform(&self) -> &Matrix4 {
+        &self.transform
+    }
+
+    pub(crate) fn with_transform(mut self, transform: Matrix4) -> Self {
+        self.inverse_transform = transform.inverse();
+        self.transform = transform;
+        self
+    }
+
+    pub fn with_sampling(mut self, sampling: Sampling) -> Self {
+        self.sampling = sampling;
+        self
+    }
+
     pub fn render(&self, world: &World) -> Canvas {
-        let pixels: Vec<_> = (0..self.vsize)
+        self.render_with_report(world).0
+    }
+
+    pub fn render_with_report(&self, world: &World) -> (Canvas, RenderReport) {
+        let (mut pixels, rays): (Vec<_>, Vec<_>) = (0..self.vsize)
             .into_par_iter()
             .flat_map(|y| {
-                (0..self.hsize).into_par_iter().map(move |x| {
-                    let ray = self.ray_for_pixel(x, y);
-                    let colour = world.colour_at(ray);
-                    (x, y, colour)
-                })
+                (0..self.hsize)
+                    .into_par_iter()
+                    .map(move |x| self.colour_at_pixel(world, x, y))
             })
-            .collect();
+            .unzip();
+
+        let mut report = RenderReport {
+            primary_rays: rays.iter().sum(),
+            extra_rays: 0,
+        };
+
+        if let Sampling::Adaptive {
+            threshold,
+            max_depth,
+        } = self.sampling
+        {
+            let refined: Vec<_> = (0..pixels.len())
+                .into_par_iter()
+                .filter(|&i| max_depth > 0 && self.has_contrasting_neighbour(&pixels, i, threshold))
+                .map(|i| {
+                    let (x, y) = ((i % self.hsize) as f64, (i / self.hsize) as f64);
+                    let mut rays = 0;
+                    let colour = self.refine(world, (x, y), 1.0, threshold, max_depth, &mut rays);
+                    (i, colour, rays)
+                })
+                .collect();
+
+            for (i, colour, rays) in refined {
+                pixels[i] = colour;
+                report.extra_rays += rays;
+            }
+        }
 
         let mut image = Canvas::new(self.hsize, self.vsize);
 
-        for (x, y, color) in pixels {
-            let _ = image.write_pixel(x, y, color);
+        for (i, color) in pixels.into_iter().enumerate() {
+            let _ = image.write_pixel(i % self.hsize, i / self.hsize, color);
         }
 
-        image
+        (image, report)
+    }
+
+    fn has_contrasting_neighbour(&self, pixels: &[Colour], i: usize, threshold: f64) -> bool {
+        let (x, y) = (i % self.hsize, i / self.hsize);
+        let neighbours = [
+            (x > 0).then(|| i - 1),
+            (x + 1 < self.hsize).then(|| i + 1),
+            (y > 0).then(|| i - self.hsize),
+            (y + 1 < self.vsize).then(|| i + self.hsize),
+        ];
+
+        neighbours
+            .iter()
+            .flatten()
+            .any(|&n| contrast(&pixels[i], &pixels[n]) > threshold)
+    }
+
+    /// Samples the centres of the four quadrants of the square at `corner` with
+    /// sides of `size` pixels, splitting any quadrant further while the samples
+    /// disagree and `depth` allows.
+    fn refine(
+        &self,
+        world: &World,
+        corner: (f64, f64),
+        size: f64,
+        threshold: f64,
+        depth: usize,
+        rays: &mut usize,
+    ) -> Colour {
+        let half = size / 2.0;
+        let quadrants = [
+            corner,
+            (corner.0 + half, corner.1),
+            (corner.0, corner.1 + half),
+            (corner.0 + half, corner.1 + half),
+        ];
+
+        let traced: Vec<_> = quadrants
+            .iter()
+            .map(|(x, y)| self.colour_at_offset(world, x + half / 2.0, y + half / 2.0))
+            .collect();
+        *rays += traced.iter().flatten().count();
+        let samples: Vec<_> = traced
+            .into_iter()
+            .map(|sample| sample.unwrap_or(Colour::new(0.0, 0.0, 0.0)))
+            .collect();
+
+        let uniform = samples
+            .iter()
+            .all(|sample| contrast(sample, &samples[0]) <= threshold);
+
+        let total = if depth <= 1 || uniform {
+            samples
+                .iter()
+                .fold(Colour::new(0.0, 0.0, 0.0), |total, sample| &total + sample)
+        } else {
+            quadrants
+                .iter()
+                .fold(Colour::new(0.0, 0.0, 0.0), |total, quadrant| {
+                    &total + &self.refine(world, *quadrant, half, threshold, depth - 1, rays)
+                })
+        };
+
+        &total * 0.25
+    }
+
+    /// The colour of pixel (`x`, `y`) and the number of rays traced for it.
+    /// Samples the projection does not cover count as black but trace no ray.
+    fn colour_at_pixel(&self, world: &World, x: usize, y: usize) -> (Colour, usize) {
+        let offsets = self.sampling.offsets(&mut sample_rng(x as f64, y as f64));
+        let mut rays = 0;
+        let total = offsets
+            .iter()
+            .filter_map(|(dx, dy)| self.colour_at_offset(world, x as f64 + dx, y as f64 + dy))
+            .fold(Colour::new(0.0, 0.0, 0.0), |total, colour| {
+                rays += 1;
+                &total + &colour
+            });
+
+        (&total * (1.0 / offsets.len() as f64), rays)
+    }
+
+    /// The colour seen through canvas position (`px`, `py`), or `None` if the
+    /// projection fires no ray there.
+    fn colour_at_offset(&self, world: &World, px: f64, py: f64) -> Option<Colour> {
+        let mut rng = sample_rng(px, py);
+        let ray = self.ray_for_offset(px, py, &mut rng)?;
+        Some(world.colour_at_with_rng(ray, &mut rng))
+    }
+
+    /// The ray through the centre of pixel (`x`, `y`), or `None` if the
+    /// projection does not cover it, such as the corners of a fisheye image.
+    pub fn ray_for_pixel(&self, x: usize, y: usize) -> Option<math::Ray> {
+        let (px, py) = (x as f64 + 0.5, y as f64 + 0.5);
+        self.ray_for_offset(px, py, &mut sample_rng(px, py))
     }
 
-    fn ray_for_pixel(&self, x: usize, y: usize) -> math::Ray {
-        let x_offset = (x as f64 + 0.5) * self.pixel_size;
-        let y_offset = (y as f64 + 0.5) * self.pixel_size;
+    /// The ray through the canvas position (`px`, `py`), measured in pixels from
+    /// the top-left corner, starting from a point on the lens drawn from `rng`.
+    fn ray_for_offset(&self, px: f64, py: f64, rng: &mut impl Rng) -> Option<math::Ray> {
+        let (origin, target) = match self.projection {
+            Projection::Perspective => self.perspective(px, py, rng),
+            Projection::Orthographic { view_width } => self.orthographic(view_width, px, py),
+            Projection::Equirectangular => self.equirectangular(px, py),
+            Projection::Fisheye { field_of_view } => self.fisheye(field_of_view, px, py)?,
+        };
+
+        let origin = &self.inverse_transform * &origin;
+        let target = &self.inverse_transform * &target;
+        let direction = (&target - &origin).normalize();
+
+        Some(Ray::new(origin, direction))
+    }
+
+    /// The origin of a perspective ray and a point it passes through, both in
+    /// camera space.
+    fn perspective(&self, px: f64, py: f64, rng: &mut impl Rng) -> (Point, Point) {
+        let x_offset = px * self.pixel_size;
+        let y_offset = py * self.pixel_size;
 
         let world_x = self.half_width - x_offset;
         let world_y = self.half_height - y_offset;
 
-        let transform_inverse = self.transform.inverse();
-        let pixel = &transform_inverse * &Point::new(world_x, world_y, -1.0);
-        let origin = &transform_inverse * &Point::new(0.0, 0.0, 0.0);
-        let direction = (&pixel - &origin).normalize();
+        if self.aperture <= 0.0 {
+            return (
+                Point::new(0.0, 0.0, 0.0),
+                Point::new(world_x, world_y, -1.0),
+            );
+        }
+
+        // Every ray through this pixel converges where the pinhole ray would meet
+        // the focal plane, wherever on the lens it starts.
+        let focal_point = Point::new(
+            world_x * self.focal_distance,
+            world_y * self.focal_distance,
+            -self.focal_distance,
+        );
+        let (lens_x, lens_y) = self.point_on_lens(rng);
+
+        (Point::new(lens_x, lens_y, 0.0), focal_point)
+    }
+
+    /// Parallel rays leaving a `view_width` wide rectangle, in camera space.
+    fn orthographic(&self, view_width: f64, px: f64, py: f64) -> (Point, Point) {
+        let pixel_size = view_width / self.hsize as f64;
+        let x = view_width / 2.0 - px * pixel_size;
+        let y = self.vsize as f64 * pixel_size / 2.0 - py * pixel_size;
+
+        (Point::new(x, y, 0.0), Point::new(x, y, -1.0))
+    }
+
+    /// Maps the canvas to longitude and latitude, covering every direction around
+    /// the camera with the centre of the canvas looking down -z.
+    fn equirectangular(&self, px: f64, py: f64) -> (Point, Point) {
+        let longitude = (px / self.hsize as f64 - 0.5) * 2.0 * PI;
+        let latitude = (0.5 - py / self.vsize as f64) * PI;
+
+        let direction = Point::new(
+            -longitude.sin() * latitude.cos(),
+            latitude.sin(),
+            -longitude.cos() * latitude.cos(),
+        );
+        (Point::new(0.0, 0.0, 0.0), direction)
+    }
+
+    /// An equidistant fisheye filling the largest circle that fits on the canvas,
+    /// where distance from the centre is proportional to the angle off axis.
+    fn fisheye(&self, field_of_view: f64, px: f64, py: f64) -> Option<(Point, Point)> {
+        let radius = self.hsize.min(self.vsize) as f64 / 2.0;
+        let x = (self.hsize as f64 / 2.0 - px) / radius;
+        let y = (self.vsize as f64 / 2.0 - py) / radius;
+
+        let distance = x.hypot(y);
+        if distance > 1.0 {
+            return None;
+        }
 
-        Ray::new(origin, direction)
+        let theta = distance * field_of_view / 2.0;
+        let phi = y.atan2(x);
+        let direction = Point::new(
+            theta.sin() * phi.cos(),
+            theta.sin() * phi.sin(),
+            -theta.cos(),
+        );
+        Some((Point::new(0.0, 0.0, 0.0), direction))
     }
+
+    fn point_on_lens(&self, rng: &mut impl Rng) -> (f64, f64) {
+        let radius = self.aperture / 2.0 * rng.gen::<f64>().sqrt();
+        let theta = 2.0 * PI * rng.gen::<f64>();
+
+        (radius * theta.cos(), radius * theta.sin())
+    }
+}
+
+/// Seeds the random choices for a sample from its canvas position, so rendering
+/// the same scene twice gives the same image however the pixels are scheduled.
+fn sample_rng(px: f64, py: f64) -> StdRng {
+    StdRng::seed_from_u64(px.to_bits().rotate_left(32) ^ py.to_bits())
+}
+
+fn contrast(a: &Colour, b: &Colour) -> f64 {
+    (a.red() - b.red())
+        .abs()
+        .max((a.green() - b.green()).abs())
+        .max((a.blue() - b.blue()).abs())
 }
 
 #[cfg(test)]
 mod test {
-    use crate::Camera;
+    use crate::camera::sample_rng;
+    use crate::{Camera, Projection, RenderReport, Sampling};
     use math::Matrix4;
 
     mod construction {
@@ -93,6 +434,17 @@ mod test {
             assert_eq!(camera.field_of_view, std::f64::consts::PI / 2.0);
             assert_eq!(camera.transform, Matrix4::identity());
         }
+        #[test]
+        fn inverse_transform_is_cached() {
+            let transform = math::Transform::default()
+                .translation(1.0, 2.0, 3.0)
+                .build();
+            let camera = Camera::new(160, 120, std::f64::consts::PI / 2.0, Matrix4::identity())
+                .with_transform(transform);
+
+            assert_eq!(camera.transform, transform);
+            assert_eq!(camera.inverse_transform, transform.inverse());
+        }
     }
 
     mod pixel_size {
@@ -120,7 +472,7 @@ mod test {
         #[test]
         fn center_of_canvas() {
             let camera = Camera::new(201, 101, std::f64::consts::PI / 2.0, Matrix4::identity());
-            let ray = camera.ray_for_pixel(100, 50);
+            let ray = camera.ray_for_pixel(100, 50).unwrap();
 
             assert_eq!(ray.origin, Point::new(0.0, 0.0, 0.0));
             assert_eq!(ray.direction, Vector::new(0.0, 0.0, -1.0));
@@ -129,7 +481,7 @@ mod test {
         #[test]
         fn corner_of_canvas() {
             let camera = Camera::new(201, 101, std::f64::consts::PI / 2.0, Matrix4::identity());
-            let ray = camera.ray_for_pixel(0, 0);
+            let ray = camera.ray_for_pixel(0, 0).unwrap();
 
             assert_eq!(ray.origin, Point::new(0.0, 0.0, 0.0));
             assert_eq!(ray.direction, Vector::new(0.66519, 0.33259, -0.66851));
@@ -142,7 +494,265 @@ mod test {
                 .rotation_y(std::f64::consts::PI / 4.0)
                 .build();
             let camera = Camera::new(201, 101, std::f64::consts::PI / 2.0, transform);
-            let ray = camera.ray_for_pixel(100, 50);
+            let ray = camera.ray_for_pixel(100, 50).unwrap();
+
+            assert_eq!(ray.origin, Point::new(0.0, 2.0, -5.0));
+            assert_eq!(
+                ray.direction,
+                Vector::new(2.0_f64.sqrt() / 2.0, 0.0, -2.0_f64.sqrt() / 2.0)
+            );
+        }
+    }
+
+    mod sampling {
+        use super::*;
+        use crate::{AreaLight, PointLight, World};
+        use core::{Colour, Point, Vector};
+        use math::Transform;
+        use shapes::{Cube, Material, Patn, Pattern, Shape};
+
+        /// A white, purely ambient box ten units in front of the camera whose left
+        /// edge sits at `left`, so that it covers the view to the right of it.
+        fn white_box(left: f64) -> Shape {
+            let material = Material {
+                ambient: 1.0,
+                diffuse: 0.0,
+                specular: 0.0,
+                ..Material::new(Pattern::new(
+                    Patn::Solid(Colour::new(1.0, 1.0, 1.0)),
+                    Matrix4::identity(),
+                ))
+            };
+            Cube::new(
+                Transform::default()
+                    .scaling(5.0, 10.0, 1.0)
+                    .translation(left + 5.0, 0.0, -10.0)
+                    .build(),
+                material,
+            )
+        }
+
+        #[test]
+        fn grid_offsets_are_sub_pixel_centres() {
+            assert_eq!(
+                Sampling::default().offsets(&mut sample_rng(0.0, 0.0)),
+                vec![(0.5, 0.5)]
+            );
+            assert_eq!(
+                Sampling::Grid(2).offsets(&mut sample_rng(0.0, 0.0)),
+                vec![(0.25, 0.25), (0.75, 0.25), (0.25, 0.75), (0.75, 0.75)]
+            );
+        }
+
+        #[test]
+        fn jittered_offsets_stay_in_their_sub_pixels() {
+            let offsets = Sampling::Jittered(3).offsets(&mut sample_rng(0.0, 0.0));
+
+            assert_eq!(offsets.len(), 9);
+            for (i, (x, y)) in offsets.iter().enumerate() {
+                let (column, row) = ((i % 3) as f64, (i / 3) as f64);
+                assert!(*x >= column / 3.0 && *x < (column + 1.0) / 3.0);
+                assert!(*y >= row / 3.0 && *y < (row + 1.0) / 3.0);
+            }
+        }
+
+        #[test]
+        fn random_offsets_stay_in_the_pixel() {
+            let offsets = Sampling::Random(16).offsets(&mut sample_rng(0.0, 0.0));
+
+            assert_eq!(Sampling::Random(16).samples_per_pixel(), 16);
+            assert_eq!(offsets.len(), 16);
+            assert!(offsets
+                .iter()
+                .all(|(x, y)| (0.0..1.0).contains(x) && (0.0..1.0).contains(y)));
+        }
+
+        #[test]
+        fn samples_are_averaged_into_the_pixel() {
+            let cube = white_box(0.0);
+            let light = PointLight::new(Point::new(0.0, 0.0, 0.0), Colour::new(1.0, 1.0, 1.0));
+            let world = World::new(vec![&cube], vec![light]);
+
+            let camera = Camera::new(1, 1, std::f64::consts::PI / 2.0, Matrix4::identity())
+                .with_sampling(Sampling::Grid(2));
+            let canvas = camera.render(&world);
+
+            assert_eq!(*canvas.pixel_at(0, 0).unwrap(), Colour::new(0.5, 0.5, 0.5));
+        }
+
+        #[test]
+        fn renders_with_jitter_are_repeatable() {
+            let cube = white_box(0.0);
+            let light = AreaLight::new(
+                Point::new(-0.5, -0.5, 0.0),
+                Vector::new(1.0, 0.0, 0.0),
+                2,
+                Vector::new(0.0, 1.0, 0.0),
+                2,
+                Colour::new(1.0, 1.0, 1.0),
+            );
+            let world = World::new(vec![&cube], vec![light]);
+
+            let camera = Camera::new(4, 4, std::f64::consts::PI / 2.0, Matrix4::identity())
+                .with_sampling(Sampling::Jittered(2))
+                .with_depth_of_field(0.1, 1.0);
+            let first = camera.render(&world);
+            let second = camera.render(&world);
+
+            for y in 0..4 {
+                for x in 0..4 {
+                    assert_eq!(first.pixel_at(x, y), second.pixel_at(x, y));
+                }
+            }
+        }
+
+        #[test]
+        fn adaptive_sampling_skips_flat_images() {
+            let world = World::new(vec![], vec![]);
+            let camera = Camera::new(4, 3, std::f64::consts::PI / 2.0, Matrix4::identity())
+                .with_sampling(Sampling::Adaptive {
+                    threshold: 0.1,
+                    max_depth: 3,
+                });
+
+            let (_, report) = camera.render_with_report(&world);
+
+            assert_eq!(
+                report,
+                RenderReport {
+                    primary_rays: 12,
+                    extra_rays: 0
+                }
+            );
+        }
+
+        #[test]
+        fn adaptive_sampling_refines_pixels_beside_an_edge() {
+            // The box's edge crosses the middle column a third of the way in, so
+            // the first two columns differ and are refined while the last is not.
+            let cube = white_box(1.0);
+            let light = PointLight::new(Point::new(0.0, 0.0, 0.0), Colour::new(1.0, 1.0, 1.0));
+            let world = World::new(vec![&cube], vec![light]);
+
+            let camera = |max_depth| {
+                Camera::new(3, 3, std::f64::consts::PI / 2.0, Matrix4::identity()).with_sampling(
+                    Sampling::Adaptive {
+                        threshold: 0.1,
+                        max_depth,
+                    },
+                )
+            };
+
+            let (canvas, report) = camera(0).render_with_report(&world);
+            assert_eq!(*canvas.pixel_at(1, 1).unwrap(), Colour::new(0.0, 0.0, 0.0));
+            assert_eq!(report.extra_rays, 0);
+
+            let (canvas, report) = camera(1).render_with_report(&world);
+            assert_eq!(*canvas.pixel_at(0, 1).unwrap(), Colour::new(1.0, 1.0, 1.0));
+            assert_eq!(*canvas.pixel_at(1, 1).unwrap(), Colour::new(0.5, 0.5, 0.5));
+            assert_eq!(*canvas.pixel_at(2, 1).unwrap(), Colour::new(0.0, 0.0, 0.0));
+            assert_eq!(report.primary_rays, 9);
+            assert_eq!(report.extra_rays, 6 * 4);
+
+            let (canvas, report) = camera(2).render_with_report(&world);
+            assert_eq!(
+                *canvas.pixel_at(1, 1).unwrap(),
+                Colour::new(0.25, 0.25, 0.25)
+            );
+            assert_eq!(report.extra_rays, 3 * 4 + 3 * (4 + 4 * 4));
+        }
+    }
+
+    mod depth_of_field {
+        use super::*;
+        use core::{Point, Vector};
+        use math::Transform;
+
+        #[test]
+        fn zero_aperture_is_a_pinhole() {
+            let camera = Camera::new(201, 101, std::f64::consts::PI / 2.0, Matrix4::identity())
+                .with_depth_of_field(0.0, 5.0);
+            let ray = camera.ray_for_pixel(0, 0).unwrap();
+
+            assert_eq!(ray.origin, Point::new(0.0, 0.0, 0.0));
+            assert_eq!(ray.direction, Vector::new(0.66519, 0.33259, -0.66851));
+        }
+
+        #[test]
+        fn rays_start_on_the_lens_and_meet_on_the_focal_plane() {
+            let transform = Transform::default()
+                .translation(0.0, -2.0, 5.0)
+                .rotation_y(std::f64::consts::PI / 4.0)
+                .build();
+            let pinhole = Camera::new(201, 101, std::f64::consts::PI / 2.0, transform);
+            let camera = Camera::new(201, 101, std::f64::consts::PI / 2.0, transform)
+                .with_depth_of_field(0.5, 4.0);
+
+            let centre = pinhole.ray_for_pixel(20, 30).unwrap();
+            let focus = centre.position(4.0 / centre.direction.dot(&camera_forward(&transform)));
+
+            let mut rng = sample_rng(20.5, 30.5);
+            for _ in 0..32 {
+                let ray = camera.ray_for_offset(20.5, 30.5, &mut rng).unwrap();
+
+                assert!((&ray.origin - &centre.origin).magnitude() <= 0.25);
+                let t = (&focus - &ray.origin).magnitude();
+                assert_eq!(ray.position(t), focus);
+            }
+        }
+
+        fn camera_forward(transform: &Matrix4) -> Vector {
+            let inverse = transform.inverse();
+            (&(&inverse * &Point::new(0.0, 0.0, -1.0)) - &(&inverse * &Point::new(0.0, 0.0, 0.0)))
+                .normalize()
+        }
+    }
+
+    mod orthographic {
+        use super::*;
+        use core::{Point, Vector};
+        use math::Transform;
+
+        fn camera(transform: Matrix4) -> Camera {
+            Camera::new(200, 100, std::f64::consts::PI / 2.0, transform)
+                .with_projection(Projection::Orthographic { view_width: 10.0 })
+        }
+
+        #[test]
+        fn rays_are_parallel() {
+            let camera = camera(Matrix4::identity());
+
+            for (x, y) in [(0, 0), (100, 50), (199, 99)] {
+                assert_eq!(
+                    camera.ray_for_pixel(x, y).unwrap().direction,
+                    Vector::new(0.0, 0.0, -1.0)
+                );
+            }
+        }
+
+        #[test]
+        fn origins_span_the_view_width() {
+            let camera = camera(Matrix4::identity());
+
+            assert_eq!(
+                camera.ray_for_pixel(0, 0).unwrap().origin,
+                Point::new(4.975, 2.475, 0.0)
+            );
+            assert_eq!(
+                camera.ray_for_pixel(199, 99).unwrap().origin,
+                Point::new(-4.975, -2.475, 0.0)
+            );
+        }
+
+        #[test]
+        fn uses_the_view_transform() {
+            let transform = Transform::default()
+                .translation(0.0, -2.0, 5.0)
+                .rotation_y(std::f64::consts::PI / 4.0)
+                .build();
+            let camera = Camera::new(201, 101, std::f64::consts::PI / 2.0, transform)
+                .with_projection(Projection::Orthographic { view_width: 10.0 });
+            let ray = camera.ray_for_pixel(100, 50).unwrap();
 
             assert_eq!(ray.origin, Point::new(0.0, 2.0, -5.0));
             assert_eq!(
@@ -151,4 +761,75 @@ mod test {
             );
         }
     }
+
+    mod panoramic {
+        use super::*;
+        use crate::World;
+        use core::{Colour, Point, Vector};
+        use std::f64::consts::{FRAC_1_SQRT_2, PI};
+
+        #[test]
+        fn equirectangular_covers_every_direction() {
+            let camera = Camera::new(200, 100, PI / 2.0, Matrix4::identity())
+                .with_projection(Projection::Equirectangular);
+
+            let cases = [
+                ((100.0, 50.0), Vector::new(0.0, 0.0, -1.0)),
+                ((50.0, 50.0), Vector::new(1.0, 0.0, 0.0)),
+                ((150.0, 50.0), Vector::new(-1.0, 0.0, 0.0)),
+                ((0.0, 50.0), Vector::new(0.0, 0.0, 1.0)),
+                ((100.0, 0.0), Vector::new(0.0, 1.0, 0.0)),
+                ((100.0, 100.0), Vector::new(0.0, -1.0, 0.0)),
+            ];
+
+            for ((px, py), expected) in cases {
+                let ray = camera
+                    .ray_for_offset(px, py, &mut sample_rng(px, py))
+                    .unwrap();
+                assert_eq!(ray.origin, Point::new(0.0, 0.0, 0.0));
+                assert_eq!(ray.direction, expected);
+            }
+        }
+
+        #[test]
+        fn hemispherical_fisheye() {
+            let camera = Camera::new(200, 100, PI / 2.0, Matrix4::identity())
+                .with_projection(Projection::Fisheye { field_of_view: PI });
+
+            let cases = [
+                ((100.0, 50.0), Vector::new(0.0, 0.0, -1.0)),
+                ((50.0, 50.0), Vector::new(1.0, 0.0, 0.0)),
+                ((100.0, 0.0), Vector::new(0.0, 1.0, 0.0)),
+                (
+                    (75.0, 50.0),
+                    Vector::new(FRAC_1_SQRT_2, 0.0, -FRAC_1_SQRT_2),
+                ),
+            ];
+
+            for ((px, py), expected) in cases {
+                assert_eq!(
+                    camera
+                        .ray_for_offset(px, py, &mut sample_rng(px, py))
+                        .unwrap()
+                        .direction,
+                    expected
+                );
+            }
+        }
+
+        #[test]
+        fn fisheye_leaves_pixels_outside_the_circle_black() {
+            let camera = Camera::new(4, 2, PI / 2.0, Matrix4::identity())
+                .with_projection(Projection::Fisheye { field_of_view: PI });
+
+            assert!(camera.ray_for_pixel(0, 0).is_none());
+            assert!(camera.ray_for_pixel(1, 0).is_some());
+
+            let world = World::new(vec![], vec![]);
+            let (canvas, report) = camera.render_with_report(&world);
+            assert_eq!(*canvas.pixel_at(0, 0).unwrap(), Colour::new(0.0, 0.0, 0.0));
+            // Only the middle two columns fall inside the circle.
+            assert_eq!(report.primary_rays, 4);
+        }
+    }
 }
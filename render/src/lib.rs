@@ -1,9 +1,15 @@
+mod bvh;
 mod camera;
 mod light;
 mod lighting;
+mod stereo;
 mod world;
 
-pub use self::camera::Camera;
-pub use self::light::PointLight;
-pub use self::lighting::lighting;
+pub use self::camera::{Camera, Projection, RenderReport, Sampling};
+pub use self::light::{
+    AreaLight, Attenuation, DirectionalLight, Light, LightSample, PointLight,
+    PolynomialAttenuation, SpotLight,
+};
+pub use self::lighting::{ambient, direct_lighting, lighting};
+pub use self::stereo::{StereoCamera, StereoLayout};
 pub use self::world::World;
@@ -1,25 +1,333 @@
-use core::{Colour, Point};
+use core::{Colour, Point, Vector};
+use rand::Rng;
+
+// Distances are clamped to this, as if each light were a small bulb rather than a
+// point, so a surface touching a light is lit at most 10,000 times as brightly as
+// one a unit away.
+const MIN_DISTANCE: f64 = 0.01;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Light {
+    Point(PointLight),
+    Area(AreaLight),
+    Spot(SpotLight),
+    Directional(DirectionalLight),
+}
+
+/// The direction from a surface point towards one sample on a light, and how far
+/// away that sample is. Directional lights are infinitely far away.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct LightSample {
+    pub direction: Vector,
+    pub distance: f64,
+}
+
+impl LightSample {
+    fn towards(from: &Point, to: &Point) -> Self {
+        let v = to - from;
+        Self {
+            direction: v.normalize(),
+            distance: v.magnitude(),
+        }
+    }
+}
+
+impl Light {
+    /// The position of the light, or `None` for lights infinitely far away.
+    pub fn position(&self) -> Option<Point> {
+        match self {
+            Light::Point(light) => Some(light.position),
+            Light::Area(light) => Some(light.position),
+            Light::Spot(light) => Some(light.position),
+            Light::Directional(_) => None,
+        }
+    }
+
+    pub fn intensity(&self) -> Colour {
+        match self {
+            Light::Point(light) => light.intensity,
+            Light::Area(light) => light.intensity,
+            Light::Spot(light) => light.intensity,
+            Light::Directional(light) => light.intensity,
+        }
+    }
+
+    /// Samples used for both shading and shadow rays at `point`. Only area lights
+    /// draw from `rng`, to jitter their samples.
+    pub fn samples_from(&self, point: &Point, rng: &mut impl Rng) -> Vec<LightSample> {
+        match self {
+            Light::Point(light) => vec![LightSample::towards(point, &light.position)],
+            Light::Area(light) => light
+                .jittered_points(rng)
+                .iter()
+                .map(|sample| LightSample::towards(point, sample))
+                .collect(),
+            Light::Spot(light) => vec![LightSample::towards(point, &light.position)],
+            Light::Directional(light) => vec![LightSample {
+                direction: -&light.direction,
+                distance: f64::INFINITY,
+            }],
+        }
+    }
+
+    /// The same light with its intensity falling off by `attenuation`. Directional
+    /// lights are infinitely far away, so they have no distance to attenuate over and
+    /// are returned unchanged.
+    pub fn with_attenuation(mut self, attenuation: Attenuation) -> Self {
+        match &mut self {
+            Light::Point(light) => light.attenuation = attenuation,
+            Light::Area(light) => light.attenuation = attenuation,
+            Light::Spot(light) => light.attenuation = attenuation,
+            Light::Directional(_) => {}
+        }
+        self
+    }
+
+    /// How much of the light's intensity survives travelling `distance` to a surface.
+    pub fn attenuation(&self, distance: f64) -> f64 {
+        match self {
+            Light::Point(light) => light.attenuation.factor(distance),
+            Light::Area(light) => light.attenuation.factor(distance),
+            Light::Spot(light) => light.attenuation.factor(distance),
+            Light::Directional(_) => 1.0,
+        }
+    }
+
+    /// How much of the light's intensity is emitted towards `point`, between 0.0 and 1.0.
+    pub fn falloff_at(&self, point: &Point) -> f64 {
+        match self {
+            Light::Point(_) | Light::Area(_) | Light::Directional(_) => 1.0,
+            Light::Spot(light) => light.falloff_at(point),
+        }
+    }
+}
+
+/// How a light's intensity falls off with the distance `d` it travels.
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub enum Attenuation {
+    #[default]
+    None,
+    /// `1 / (constant + linear * d + quadratic * d²)`
+    Polynomial(PolynomialAttenuation),
+    /// `1 / d²`, as for a physical point source.
+    InverseSquare,
+}
+
+impl Attenuation {
+    /// Polynomial attenuation, provided the coefficients are finite, none is
+    /// negative and at least one is positive, so the light never turns infinite,
+    /// undefined or negative.
+    pub fn polynomial(constant: f64, linear: f64, quadratic: f64) -> Result<Self, &'static str> {
+        let coefficients = [constant, linear, quadratic];
+        if coefficients.iter().any(|c| !c.is_finite() || *c < 0.0) {
+            return Err("attenuation coefficients must be finite and non-negative");
+        }
+        if coefficients.iter().all(|c| *c == 0.0) {
+            return Err("at least one attenuation coefficient must be positive");
+        }
+
+        Ok(Attenuation::Polynomial(PolynomialAttenuation {
+            constant,
+            linear,
+            quadratic,
+        }))
+    }
+
+    /// The fraction of the light left after `distance`. Distances below
+    /// `MIN_DISTANCE` count as `MIN_DISTANCE`.
+    pub fn factor(&self, distance: f64) -> f64 {
+        let distance = distance.max(MIN_DISTANCE);
+        match self {
+            Attenuation::None => 1.0,
+            Attenuation::Polynomial(polynomial) => {
+                1.0 / (polynomial.constant
+                    + polynomial.linear * distance
+                    + polynomial.quadratic * distance * distance)
+            }
+            Attenuation::InverseSquare => 1.0 / distance.powi(2),
+        }
+    }
+}
+
+/// Coefficients for `Attenuation::Polynomial`, checked by `Attenuation::polynomial`.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct PolynomialAttenuation {
+    constant: f64,
+    linear: f64,
+    quadratic: f64,
+}
 
 #[derive(Debug, PartialEq, Clone, Copy)]
 pub struct PointLight {
     pub position: Point,
     pub intensity: Colour,
+    pub attenuation: Attenuation,
 }
 
 impl PointLight {
-    pub fn new(position: Point, intensity: Colour) -> Self {
-        Self {
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(position: Point, intensity: Colour) -> Light {
+        PointLight::attenuated(position, intensity, Attenuation::None)
+    }
+
+    pub fn attenuated(position: Point, intensity: Colour, attenuation: Attenuation) -> Light {
+        Light::Point(Self {
+            position,
+            intensity,
+            attenuation,
+        })
+    }
+}
+
+/// A rectangular light spanning `uvec` and `vvec` from `corner`, divided into a
+/// `usteps` by `vsteps` grid of cells that are each sampled once per query.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct AreaLight {
+    pub corner: Point,
+    pub uvec: Vector,
+    pub usteps: usize,
+    pub vvec: Vector,
+    pub vsteps: usize,
+    pub position: Point,
+    pub intensity: Colour,
+    pub attenuation: Attenuation,
+}
+
+impl AreaLight {
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(
+        corner: Point,
+        full_uvec: Vector,
+        usteps: usize,
+        full_vvec: Vector,
+        vsteps: usize,
+        intensity: Colour,
+    ) -> Light {
+        let usteps = usteps.max(1);
+        let vsteps = vsteps.max(1);
+        let position = &(&corner + &(&full_uvec * 0.5)) + &(&full_vvec * 0.5);
+
+        Light::Area(Self {
+            corner,
+            uvec: &full_uvec * (1.0 / usteps as f64),
+            usteps,
+            vvec: &full_vvec * (1.0 / vsteps as f64),
+            vsteps,
+            position,
+            intensity,
+            attenuation: Attenuation::None,
+        })
+    }
+
+    /// The number of cells, each sampled once per query.
+    pub fn samples(&self) -> usize {
+        self.usteps * self.vsteps
+    }
+
+    /// The point in cell (`u`, `v`), with `jitter` giving the offset inside the
+    /// cell along each axis in the range [0, 1).
+    pub fn point_on_light(&self, u: usize, v: usize, jitter: (f64, f64)) -> Point {
+        &(&self.corner + &(&self.uvec * (u as f64 + jitter.0)))
+            + &(&self.vvec * (v as f64 + jitter.1))
+    }
+
+    fn jittered_points(&self, rng: &mut impl Rng) -> Vec<Point> {
+        (0..self.vsteps)
+            .flat_map(|v| (0..self.usteps).map(move |u| (u, v)))
+            .map(|(u, v)| self.point_on_light(u, v, (rng.gen(), rng.gen())))
+            .collect()
+    }
+}
+
+/// A point light restricted to a cone around `direction`. Points within
+/// `inner_angle` of the axis are fully lit, and the intensity eases to zero at
+/// `outer_angle`. Both angles are in radians, measured from the axis.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct SpotLight {
+    pub position: Point,
+    pub direction: Vector,
+    pub inner_angle: f64,
+    pub outer_angle: f64,
+    pub intensity: Colour,
+    pub attenuation: Attenuation,
+}
+
+impl SpotLight {
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(
+        position: Point,
+        direction: Vector,
+        inner_angle: f64,
+        outer_angle: f64,
+        intensity: Colour,
+    ) -> Light {
+        Light::Spot(Self {
             position,
+            direction: direction.normalize(),
+            inner_angle: inner_angle.min(outer_angle),
+            outer_angle,
             intensity,
+            attenuation: Attenuation::None,
+        })
+    }
+
+    fn falloff_at(&self, point: &Point) -> f64 {
+        let offset = point - &self.position;
+        // A point at the light itself has no direction from it; treat it as the
+        // brightest part of the beam rather than normalizing a zero vector.
+        if offset.magnitude() == 0.0 {
+            return 1.0;
+        }
+
+        let cos_angle = offset.normalize().dot(&self.direction);
+        let cos_inner = self.inner_angle.cos();
+        let cos_outer = self.outer_angle.cos();
+
+        if cos_angle >= cos_inner {
+            return 1.0;
+        }
+        if cos_angle <= cos_outer {
+            return 0.0;
         }
+
+        let t = (cos_angle - cos_outer) / (cos_inner - cos_outer);
+        t * t * (3.0 - 2.0 * t)
+    }
+}
+
+/// A light infinitely far away, such as the sun, whose rays all travel in
+/// `direction`.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct DirectionalLight {
+    pub direction: Vector,
+    pub intensity: Colour,
+}
+
+impl DirectionalLight {
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(direction: Vector, intensity: Colour) -> Light {
+        Light::Directional(Self {
+            direction: direction.normalize(),
+            intensity,
+        })
     }
 }
 
 #[cfg(test)]
 mod test {
-    use crate::PointLight;
+    use crate::light::MIN_DISTANCE;
+    use crate::{
+        AreaLight, Attenuation, DirectionalLight, Light, LightSample, PointLight, SpotLight,
+    };
     use core::Colour;
     use core::Point;
+    use core::Vector;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn rng() -> StdRng {
+        StdRng::seed_from_u64(0)
+    }
 
     #[test]
     fn a_point_light_has_a_position_and_intensity() {
@@ -27,7 +335,282 @@ mod test {
         let position = Point::new(0.0, 0.0, 0.0);
         let light = PointLight::new(position, intensity);
 
-        assert_eq!(light.position, position);
-        assert_eq!(light.intensity, intensity);
+        assert_eq!(light.position(), Some(position));
+        assert_eq!(light.intensity(), intensity);
+        assert_eq!(
+            light.samples_from(&Point::new(0.0, 0.0, -2.0), &mut rng()),
+            vec![LightSample {
+                direction: Vector::new(0.0, 0.0, 1.0),
+                distance: 2.0,
+            }]
+        );
+    }
+
+    mod attenuation {
+        use super::*;
+
+        #[test]
+        fn point_lights_are_not_attenuated_by_default() {
+            let light = PointLight::new(Point::new(0.0, 0.0, 0.0), Colour::new(1.0, 1.0, 1.0));
+
+            assert_eq!(light.attenuation(1.0), 1.0);
+            assert_eq!(light.attenuation(100.0), 1.0);
+        }
+
+        #[test]
+        fn polynomial_attenuation() {
+            let attenuation = Attenuation::polynomial(1.0, 0.5, 0.25).unwrap();
+
+            assert!((attenuation.factor(0.0) - 1.0).abs() < 0.01);
+            assert_eq!(attenuation.factor(2.0), 1.0 / 3.0);
+            assert_eq!(attenuation.factor(4.0), 1.0 / 7.0);
+        }
+
+        #[test]
+        fn inverse_square_attenuation() {
+            let light = PointLight::attenuated(
+                Point::new(0.0, 0.0, 0.0),
+                Colour::new(1.0, 1.0, 1.0),
+                Attenuation::InverseSquare,
+            );
+
+            assert_eq!(light.attenuation(1.0), 1.0);
+            assert_eq!(light.attenuation(2.0), 0.25);
+            assert_eq!(light.attenuation(10.0), 0.01);
+        }
+
+        #[test]
+        fn polynomial_coefficients_are_checked() {
+            assert!(Attenuation::polynomial(0.0, 0.0, 0.0).is_err());
+            assert!(Attenuation::polynomial(1.0, -0.5, 0.0).is_err());
+            assert!(Attenuation::polynomial(f64::NAN, 0.0, 1.0).is_err());
+            assert!(Attenuation::polynomial(0.0, 0.0, 1.0).is_ok());
+        }
+
+        #[test]
+        fn attenuation_is_finite_at_the_light() {
+            for attenuation in [
+                Attenuation::InverseSquare,
+                Attenuation::polynomial(0.0, 1.0, 0.0).unwrap(),
+            ] {
+                assert!(attenuation.factor(0.0).is_finite());
+                assert_eq!(attenuation.factor(0.0), attenuation.factor(MIN_DISTANCE));
+            }
+        }
+
+        #[test]
+        fn spot_and_area_lights_are_attenuated() {
+            let white = Colour::new(1.0, 1.0, 1.0);
+            let lights = [
+                SpotLight::new(
+                    Point::new(0.0, 0.0, 0.0),
+                    Vector::new(0.0, -1.0, 0.0),
+                    0.5,
+                    1.0,
+                    white,
+                ),
+                AreaLight::new(
+                    Point::new(0.0, 0.0, 0.0),
+                    Vector::new(1.0, 0.0, 0.0),
+                    2,
+                    Vector::new(0.0, 0.0, 1.0),
+                    2,
+                    white,
+                ),
+            ];
+
+            for light in lights {
+                assert_eq!(light.attenuation(2.0), 1.0);
+                let light = light.with_attenuation(Attenuation::InverseSquare);
+                assert_eq!(light.attenuation(2.0), 0.25);
+            }
+        }
+
+        #[test]
+        fn directional_lights_ignore_attenuation() {
+            let light =
+                DirectionalLight::new(Vector::new(0.0, -1.0, 0.0), Colour::new(1.0, 1.0, 1.0))
+                    .with_attenuation(Attenuation::InverseSquare);
+
+            assert_eq!(light.attenuation(10.0), 1.0);
+        }
+    }
+
+    mod area_light {
+        use super::*;
+
+        fn area_light() -> AreaLight {
+            match AreaLight::new(
+                Point::new(0.0, 0.0, 0.0),
+                Vector::new(2.0, 0.0, 0.0),
+                4,
+                Vector::new(0.0, 0.0, 1.0),
+                2,
+                Colour::new(1.0, 1.0, 1.0),
+            ) {
+                Light::Area(light) => light,
+                light => panic!("expected an area light, got {:?}", light),
+            }
+        }
+
+        #[test]
+        fn creating_an_area_light() {
+            let light = area_light();
+
+            assert_eq!(light.corner, Point::new(0.0, 0.0, 0.0));
+            assert_eq!(light.uvec, Vector::new(0.5, 0.0, 0.0));
+            assert_eq!(light.usteps, 4);
+            assert_eq!(light.vvec, Vector::new(0.0, 0.0, 0.5));
+            assert_eq!(light.vsteps, 2);
+            assert_eq!(light.samples(), 8);
+            assert_eq!(light.position, Point::new(1.0, 0.0, 0.5));
+        }
+
+        #[test]
+        fn finding_a_single_point_on_an_area_light() {
+            let light = area_light();
+
+            let cases = [
+                (0, 0, Point::new(0.25, 0.0, 0.25)),
+                (1, 0, Point::new(0.75, 0.0, 0.25)),
+                (0, 1, Point::new(0.25, 0.0, 0.75)),
+                (2, 0, Point::new(1.25, 0.0, 0.25)),
+                (3, 1, Point::new(1.75, 0.0, 0.75)),
+            ];
+
+            for (u, v, expected) in cases {
+                assert_eq!(light.point_on_light(u, v, (0.5, 0.5)), expected);
+            }
+        }
+
+        #[test]
+        fn samples_are_jittered_within_their_cells() {
+            let points = area_light().jittered_points(&mut rng());
+
+            assert_eq!(points.len(), 8);
+            for (i, point) in points.iter().enumerate() {
+                let (u, v) = ((i % 4) as f64, (i / 4) as f64);
+                assert!(point.x() >= u * 0.5 && point.x() < (u + 1.0) * 0.5);
+                assert!(point.z() >= v * 0.5 && point.z() < (v + 1.0) * 0.5);
+                assert_eq!(point.y(), 0.0);
+            }
+        }
+
+        #[test]
+        fn jitter_is_repeatable_for_a_seed() {
+            let light = area_light();
+
+            assert_eq!(
+                light.jittered_points(&mut rng()),
+                light.jittered_points(&mut rng())
+            );
+            assert_ne!(
+                light.jittered_points(&mut rng()),
+                light.jittered_points(&mut StdRng::seed_from_u64(1))
+            );
+        }
+    }
+
+    mod spot_light {
+        use super::*;
+        use std::f64::consts::FRAC_PI_4;
+
+        fn spot_light() -> Light {
+            SpotLight::new(
+                Point::new(0.0, 10.0, 0.0),
+                Vector::new(0.0, -2.0, 0.0),
+                FRAC_PI_4 / 2.0,
+                FRAC_PI_4,
+                Colour::new(1.0, 1.0, 1.0),
+            )
+        }
+
+        #[test]
+        fn creating_a_spot_light() {
+            let light = spot_light();
+
+            assert_eq!(light.position(), Some(Point::new(0.0, 10.0, 0.0)));
+            assert_eq!(light.intensity(), Colour::new(1.0, 1.0, 1.0));
+            match light {
+                Light::Spot(spot) => assert_eq!(spot.direction, Vector::new(0.0, -1.0, 0.0)),
+                light => panic!("expected a spot light, got {:?}", light),
+            }
+        }
+
+        #[test]
+        fn point_at_the_light_is_fully_lit() {
+            let light = spot_light();
+
+            assert_eq!(light.falloff_at(&Point::new(0.0, 10.0, 0.0)), 1.0);
+        }
+
+        #[test]
+        fn point_inside_the_inner_cone_is_fully_lit() {
+            let light = spot_light();
+
+            assert_eq!(light.falloff_at(&Point::new(0.0, 0.0, 0.0)), 1.0);
+            assert_eq!(light.falloff_at(&Point::new(3.0, 0.0, 0.0)), 1.0);
+        }
+
+        #[test]
+        fn point_outside_the_outer_cone_is_unlit() {
+            let light = spot_light();
+
+            assert_eq!(light.falloff_at(&Point::new(10.5, 0.0, 0.0)), 0.0);
+            assert_eq!(light.falloff_at(&Point::new(0.0, 20.0, 0.0)), 0.0);
+        }
+
+        #[test]
+        fn intensity_eases_between_the_cones() {
+            let light = spot_light();
+
+            let falloffs: Vec<f64> = [4.5, 6.0, 7.5, 9.0]
+                .iter()
+                .map(|x| light.falloff_at(&Point::new(*x, 0.0, 0.0)))
+                .collect();
+
+            assert!(falloffs.windows(2).all(|pair| pair[0] > pair[1]));
+            assert!(falloffs.iter().all(|f| *f > 0.0 && *f < 1.0));
+        }
+
+        #[test]
+        fn other_lights_have_no_falloff() {
+            let light = PointLight::new(Point::new(0.0, 10.0, 0.0), Colour::new(1.0, 1.0, 1.0));
+
+            assert_eq!(light.falloff_at(&Point::new(100.0, 0.0, 0.0)), 1.0);
+        }
+    }
+
+    mod directional_light {
+        use super::*;
+
+        #[test]
+        fn creating_a_directional_light() {
+            let light =
+                DirectionalLight::new(Vector::new(0.0, -2.0, 0.0), Colour::new(1.0, 1.0, 1.0));
+
+            assert_eq!(light.position(), None);
+            assert_eq!(light.intensity(), Colour::new(1.0, 1.0, 1.0));
+            assert_eq!(light.falloff_at(&Point::new(100.0, 0.0, 0.0)), 1.0);
+        }
+
+        #[test]
+        fn samples_point_against_the_light_direction() {
+            let light =
+                DirectionalLight::new(Vector::new(1.0, -1.0, 0.0), Colour::new(1.0, 1.0, 1.0));
+            let expected = vec![LightSample {
+                direction: Vector::new(-1.0, 1.0, 0.0).normalize(),
+                distance: f64::INFINITY,
+            }];
+
+            assert_eq!(
+                light.samples_from(&Point::new(0.0, 0.0, 0.0), &mut rng()),
+                expected
+            );
+            assert_eq!(
+                light.samples_from(&Point::new(50.0, 3.0, -20.0), &mut rng()),
+                expected
+            );
+        }
     }
 }
@@ -1,44 +1,78 @@
-use crate::PointLight;
+use crate::{Light, LightSample};
 use core::{Colour, Point, Vector};
+use rand::Rng;
 use shapes::{Material, Shape};
 
+/// Phong shading for a single light. `light_intensity` is the fraction of the
+/// light that reaches `point`, from 0.0 when fully shadowed to 1.0 when fully lit.
+/// Area lights jitter their samples with `rng`.
+#[allow(clippy::too_many_arguments)]
 pub fn lighting(
     shape: &Shape,
     material: &Material,
-    light: &PointLight,
+    light: &Light,
     point: &Point,
     eyev: &Vector,
     normalv: &Vector,
-    in_shadow: bool,
+    light_intensity: f64,
+    rng: &mut impl Rng,
 ) -> Colour {
-    let effective_colour = &shape.colour_at(point) * &light.intensity;
-    let ambient = &effective_colour * material.ambient;
+    let ambient = ambient(shape, material, &light.intensity(), point);
+    let samples = light.samples_from(point, rng);
+    let direct = direct_lighting(shape, material, light, point, eyev, normalv, &samples);
 
-    if in_shadow {
-        return ambient;
+    &ambient + &(&direct * light_intensity)
+}
+
+pub fn ambient(shape: &Shape, material: &Material, intensity: &Colour, point: &Point) -> Colour {
+    &(&shape.colour_at(point) * intensity) * material.ambient
+}
+
+/// The diffuse and specular contribution of `light`, averaged over `samples` taken
+/// from it with `Light::samples_from`.
+pub fn direct_lighting(
+    shape: &Shape,
+    material: &Material,
+    light: &Light,
+    point: &Point,
+    eyev: &Vector,
+    normalv: &Vector,
+    samples: &[LightSample],
+) -> Colour {
+    let falloff = light.falloff_at(point);
+    if falloff == 0.0 || samples.is_empty() {
+        return Colour::new(0.0, 0.0, 0.0);
     }
 
-    let lightv = (&light.position - point).normalize();
-    let light_dot_normal = lightv.dot(normalv);
+    let intensity = &light.intensity() * falloff;
+    let effective_colour = &shape.colour_at(point) * &intensity;
 
-    let (diffuse, specular) = if light_dot_normal < 0.0 {
-        (Colour::new(0.0, 0.0, 0.0), Colour::new(0.0, 0.0, 0.0))
-    } else {
-        let diffuse = &(&effective_colour * material.diffuse) * light_dot_normal;
-        let reflectv = -&lightv.reflect(normalv);
-        let reflect_dot_eye = reflectv.dot(eyev);
+    let total = samples
+        .iter()
+        .fold(Colour::new(0.0, 0.0, 0.0), |total, sample| {
+            let lightv = sample.direction;
+            let light_dot_normal = lightv.dot(normalv);
 
-        let specular = if reflect_dot_eye <= 0.0 {
-            Colour::new(0.0, 0.0, 0.0)
-        } else {
-            let factor = reflect_dot_eye.powf(material.shininess);
-            &(&light.intensity * material.specular) * factor
-        };
+            if light_dot_normal < 0.0 {
+                return total;
+            }
 
-        (diffuse, specular)
-    };
+            let diffuse = &(&effective_colour * material.diffuse) * light_dot_normal;
+            let reflectv = -&lightv.reflect(normalv);
+            let reflect_dot_eye = reflectv.dot(eyev);
 
-    &(&ambient + &diffuse) + &specular
+            let specular = if reflect_dot_eye <= 0.0 {
+                Colour::new(0.0, 0.0, 0.0)
+            } else {
+                let factor = reflect_dot_eye.powf(material.shininess);
+                &(&intensity * material.specular) * factor
+            };
+
+            let attenuation = light.attenuation(sample.distance);
+            &total + &(&(&diffuse + &specular) * attenuation)
+        });
+
+    &total * (1.0 / samples.len() as f64)
 }
 
 #[cfg(test)]
@@ -49,8 +83,15 @@ mod tests {
     use core::Point;
     use core::Vector;
     use math::Matrix4;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
     use shapes::Material;
     use shapes::Sphere;
+
+    fn rng() -> StdRng {
+        StdRng::seed_from_u64(0)
+    }
+
     mod lighting {
         use super::*;
 
@@ -63,7 +104,8 @@ mod tests {
                 &Point::new(0.0, 0.0, 0.0),
                 &Vector::new(0.0, 0.0, -1.0),
                 &Vector::new(0.0, 0.0, -1.0),
-                false,
+                1.0,
+                &mut rng(),
             );
 
             assert_eq!(result, Colour::new(1.9, 1.9, 1.9));
@@ -78,7 +120,8 @@ mod tests {
                 &Point::new(0.0, 0.0, 0.0),
                 &Vector::new(0.0, 2.0_f64.sqrt() / 2.0, -2.0_f64.sqrt() / 2.0),
                 &Vector::new(0.0, 0.0, -1.0),
-                false,
+                1.0,
+                &mut rng(),
             );
 
             assert_eq!(result, Colour::new(1.0, 1.0, 1.0));
@@ -93,7 +136,8 @@ mod tests {
                 &Point::new(0.0, 0.0, 0.0),
                 &Vector::new(0.0, 0.0, -1.0),
                 &Vector::new(0.0, 0.0, -1.0),
-                false,
+                1.0,
+                &mut rng(),
             );
 
             assert_eq!(result, Colour::new(0.7364, 0.7364, 0.7364));
@@ -108,7 +152,8 @@ mod tests {
                 &Point::new(0.0, 0.0, 0.0),
                 &Vector::new(0.0, -2.0_f64.sqrt() / 2.0, -2.0_f64.sqrt() / 2.0),
                 &Vector::new(0.0, 0.0, -1.0),
-                false,
+                1.0,
+                &mut rng(),
             );
 
             assert_eq!(result, Colour::new(1.6364, 1.6364, 1.6364));
@@ -123,13 +168,116 @@ mod tests {
                 &Point::new(0.0, 0.0, 0.0),
                 &Vector::new(0.0, 0.0, -1.0),
                 &Vector::new(0.0, 0.0, -1.0),
-                false,
+                1.0,
+                &mut rng(),
             );
 
             assert_eq!(result, Colour::new(0.1, 0.1, 0.1));
         }
     }
 
+    mod spot_light {
+        use super::*;
+        use crate::SpotLight;
+        use std::f64::consts::FRAC_PI_4;
+
+        fn spot_light(direction: Vector) -> crate::Light {
+            SpotLight::new(
+                Point::new(0.0, 0.0, -10.0),
+                direction,
+                FRAC_PI_4 / 2.0,
+                FRAC_PI_4,
+                Colour::new(1.0, 1.0, 1.0),
+            )
+        }
+
+        #[test]
+        fn surface_inside_the_cone() {
+            let result = lighting(
+                &Sphere::new(Matrix4::identity(), Material::default()),
+                &Material::default(),
+                &spot_light(Vector::new(0.0, 0.0, 1.0)),
+                &Point::new(0.0, 0.0, 0.0),
+                &Vector::new(0.0, 0.0, -1.0),
+                &Vector::new(0.0, 0.0, -1.0),
+                1.0,
+                &mut rng(),
+            );
+
+            assert_eq!(result, Colour::new(1.9, 1.9, 1.9));
+        }
+
+        #[test]
+        fn surface_outside_the_cone() {
+            let result = lighting(
+                &Sphere::new(Matrix4::identity(), Material::default()),
+                &Material::default(),
+                &spot_light(Vector::new(0.0, 1.0, 0.0)),
+                &Point::new(0.0, 0.0, 0.0),
+                &Vector::new(0.0, 0.0, -1.0),
+                &Vector::new(0.0, 0.0, -1.0),
+                1.0,
+                &mut rng(),
+            );
+
+            assert_eq!(result, Colour::new(0.1, 0.1, 0.1));
+        }
+    }
+
+    mod directional_light {
+        use super::*;
+        use crate::DirectionalLight;
+
+        #[test]
+        fn light_vector_is_the_same_everywhere() {
+            let light =
+                DirectionalLight::new(Vector::new(0.0, 0.0, 1.0), Colour::new(1.0, 1.0, 1.0));
+
+            for z in [-1.0, -100.0, -10000.0] {
+                let result = lighting(
+                    &Sphere::new(Matrix4::identity(), Material::default()),
+                    &Material::default(),
+                    &light,
+                    &Point::new(0.0, 0.0, z),
+                    &Vector::new(0.0, 0.0, -1.0),
+                    &Vector::new(0.0, 0.0, -1.0),
+                    1.0,
+                    &mut rng(),
+                );
+
+                assert_eq!(result, Colour::new(1.9, 1.9, 1.9));
+            }
+        }
+    }
+
+    mod attenuation {
+        use super::*;
+        use crate::Attenuation;
+
+        #[test]
+        fn attenuation_scales_diffuse_and_specular() {
+            let light = PointLight::attenuated(
+                Point::new(0.0, 0.0, -2.0),
+                Colour::new(1.0, 1.0, 1.0),
+                Attenuation::InverseSquare,
+            );
+
+            let result = lighting(
+                &Sphere::new(Matrix4::identity(), Material::default()),
+                &Material::default(),
+                &light,
+                &Point::new(0.0, 0.0, 0.0),
+                &Vector::new(0.0, 0.0, -1.0),
+                &Vector::new(0.0, 0.0, -1.0),
+                1.0,
+                &mut rng(),
+            );
+
+            // Ambient 0.1 is untouched; diffuse 0.9 and specular 0.9 are quartered.
+            assert_eq!(result, Colour::new(0.55, 0.55, 0.55));
+        }
+    }
+
     mod shadow {
         use super::*;
         #[test]
@@ -141,10 +289,35 @@ mod tests {
                 &Point::new(0.0, 0.0, 0.0),
                 &Vector::new(0.0, 0.0, -1.0),
                 &Vector::new(0.0, 0.0, -1.0),
-                true,
+                0.0,
+                &mut rng(),
             );
 
             assert_eq!(result, Colour::new(0.1, 0.1, 0.1));
         }
+
+        #[test]
+        fn light_intensity_scales_diffuse_and_specular() {
+            let material = Material {
+                specular: 0.0,
+                ..Default::default()
+            };
+            let cases = [(1.0, 1.0), (0.5, 0.55), (0.0, 0.1)];
+
+            for (intensity, expected) in cases {
+                let result = lighting(
+                    &Sphere::new(Matrix4::identity(), material),
+                    &material,
+                    &PointLight::new(Point::new(0.0, 0.0, -10.0), Colour::new(1.0, 1.0, 1.0)),
+                    &Point::new(0.0, 0.0, -1.0),
+                    &Vector::new(0.0, 0.0, -1.0),
+                    &Vector::new(0.0, 0.0, -1.0),
+                    intensity,
+                    &mut rng(),
+                );
+
+                assert_eq!(result, Colour::new(expected, expected, expected));
+            }
+        }
     }
 }
@@ -0,0 +1,183 @@
+use crate::{Camera, World};
+use core::{Colour, Point, Vector};
+use math::Transform;
+use output::Canvas;
+
+/// How the two eye views of a stereo render are combined into one image.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum StereoLayout {
+    /// The left eye's view beside the right eye's, in a canvas twice as wide.
+    SideBySide,
+    /// Red from the left eye and green and blue from the right, for red/cyan glasses.
+    Anaglyph,
+}
+
+impl StereoLayout {
+    pub fn compose(&self, left: &Canvas, right: &Canvas) -> Result<Canvas, String> {
+        if left.width != right.width || left.height != right.height {
+            return Err(format!(
+                "Eye views must be the same size, got {}x{} and {}x{}.",
+                left.width, left.height, right.width, right.height
+            ));
+        }
+
+        let (width, height) = (left.width, left.height);
+        match self {
+            StereoLayout::SideBySide => {
+                let mut image = Canvas::new(width * 2, height);
+                for y in 0..height {
+                    for x in 0..width {
+                        image.write_pixel(x, y, *left.pixel_at(x, y)?)?;
+                        image.write_pixel(width + x, y, *right.pixel_at(x, y)?)?;
+                    }
+                }
+                Ok(image)
+            }
+            StereoLayout::Anaglyph => {
+                let mut image = Canvas::new(width, height);
+                for y in 0..height {
+                    for x in 0..width {
+                        let (l, r) = (left.pixel_at(x, y)?, right.pixel_at(x, y)?);
+                        image.write_pixel(x, y, Colour::new(l.red(), r.green(), r.blue()))?;
+                    }
+                }
+                Ok(image)
+            }
+        }
+    }
+}
+
+/// A pair of cameras `interocular` units apart either side of a centre camera,
+/// each turned inwards so that their views converge `convergence` units ahead.
+#[derive(Debug, Clone)]
+pub struct StereoCamera {
+    pub left: Camera,
+    pub right: Camera,
+}
+
+impl StereoCamera {
+    pub fn new(camera: Camera, interocular: f64, convergence: f64) -> Self {
+        // In camera space the eye looks down -z and +x is to its left.
+        let eye = |x: f64| {
+            let view = Transform::default()
+                .view_transform(
+                    Point::new(x, 0.0, 0.0),
+                    Point::new(0.0, 0.0, -convergence),
+                    Vector::new(0.0, 1.0, 0.0),
+                )
+                .build();
+            let transform = &view * camera.transform();
+            camera.clone().with_transform(transform)
+        };
+
+        Self {
+            left: eye(interocular / 2.0),
+            right: eye(-interocular / 2.0),
+        }
+    }
+
+    pub fn render(&self, world: &World, layout: StereoLayout) -> Canvas {
+        let left = self.left.render(world);
+        let right = self.right.render(world);
+
+        layout
+            .compose(&left, &right)
+            .expect("both eyes share the centre camera's canvas size")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Camera, StereoCamera, StereoLayout};
+    use core::{Colour, Point, Vector};
+    use math::{Matrix4, Transform};
+    use output::Canvas;
+
+    fn canvas(width: usize, height: usize, colour: Colour) -> Canvas {
+        let mut canvas = Canvas::new(width, height);
+        for y in 0..height {
+            for x in 0..width {
+                canvas.write_pixel(x, y, colour).unwrap();
+            }
+        }
+        canvas
+    }
+
+    mod rig {
+        use super::*;
+
+        #[test]
+        fn eyes_are_offset_and_converge() {
+            let camera = Camera::new(201, 101, std::f64::consts::PI / 2.0, Matrix4::identity());
+            let stereo = StereoCamera::new(camera, 0.2, 5.0);
+
+            let left = stereo.left.ray_for_pixel(100, 50).unwrap();
+            let right = stereo.right.ray_for_pixel(100, 50).unwrap();
+
+            assert_eq!(left.origin, Point::new(0.1, 0.0, 0.0));
+            assert_eq!(right.origin, Point::new(-0.1, 0.0, 0.0));
+            assert_eq!(left.direction, Vector::new(-0.1, 0.0, -5.0).normalize());
+            assert_eq!(right.direction, Vector::new(0.1, 0.0, -5.0).normalize());
+        }
+
+        #[test]
+        fn eyes_follow_the_view_transform() {
+            let transform = Transform::default()
+                .view_transform(
+                    Point::new(5.0, 1.0, 0.0),
+                    Point::new(0.0, 1.0, 0.0),
+                    Vector::new(0.0, 1.0, 0.0),
+                )
+                .build();
+            let camera = Camera::new(201, 101, std::f64::consts::PI / 2.0, transform);
+            let stereo = StereoCamera::new(camera, 0.2, 5.0);
+
+            let left = stereo.left.ray_for_pixel(100, 50).unwrap();
+            let right = stereo.right.ray_for_pixel(100, 50).unwrap();
+
+            // Looking down -x from x = 5, the camera's left is -z.
+            assert_eq!(left.origin, Point::new(5.0, 1.0, -0.1));
+            assert_eq!(right.origin, Point::new(5.0, 1.0, 0.1));
+            assert_eq!(left.position(5.0_f64.hypot(0.1)), Point::new(0.0, 1.0, 0.0));
+            assert_eq!(
+                right.position(5.0_f64.hypot(0.1)),
+                Point::new(0.0, 1.0, 0.0)
+            );
+        }
+    }
+
+    mod layout {
+        use super::*;
+
+        #[test]
+        fn side_by_side() {
+            let left = canvas(2, 1, Colour::new(1.0, 0.0, 0.0));
+            let right = canvas(2, 1, Colour::new(0.0, 0.0, 1.0));
+
+            let image = StereoLayout::SideBySide.compose(&left, &right).unwrap();
+
+            assert_eq!((image.width, image.height), (4, 1));
+            assert_eq!(*image.pixel_at(1, 0).unwrap(), Colour::new(1.0, 0.0, 0.0));
+            assert_eq!(*image.pixel_at(2, 0).unwrap(), Colour::new(0.0, 0.0, 1.0));
+        }
+
+        #[test]
+        fn anaglyph() {
+            let left = canvas(2, 2, Colour::new(0.2, 0.4, 0.6));
+            let right = canvas(2, 2, Colour::new(0.7, 0.8, 0.9));
+
+            let image = StereoLayout::Anaglyph.compose(&left, &right).unwrap();
+
+            assert_eq!((image.width, image.height), (2, 2));
+            assert_eq!(*image.pixel_at(1, 1).unwrap(), Colour::new(0.2, 0.8, 0.9));
+        }
+
+        #[test]
+        fn mismatched_eye_views() {
+            let left = canvas(2, 2, Colour::new(0.0, 0.0, 0.0));
+            let right = canvas(3, 2, Colour::new(0.0, 0.0, 0.0));
+
+            assert!(StereoLayout::Anaglyph.compose(&left, &right).is_err());
+        }
+    }
+}
@@ -1,64 +1,221 @@
-use crate::{lighting, PointLight};
+use crate::bvh::Bvh;
+use crate::{ambient, direct_lighting, Light, LightSample};
 use core::{Colour, Point};
 use math::Ray;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 use shapes::{find_hit, Computations, Intersection, Shape};
 
 pub struct World<'a> {
-    pub shapes: Vec<&'a Shape>,
-    pub light: PointLight,
+    // Private so that the BVH built from them in `new` can never go stale.
+    shapes: Vec<&'a Shape>,
+    pub lights: Vec<Light>,
+    pub max_depth: usize,
+    bvh: Bvh<'a>,
 }
 
 impl<'a> World<'a> {
-    pub fn new(shapes: Vec<&'a Shape>, light: PointLight) -> Self {
-        Self { shapes, light }
+    const DEFAULT_MAX_DEPTH: usize = 5;
+
+    pub fn new(shapes: Vec<&'a Shape>, lights: Vec<Light>) -> Self {
+        let bvh = Bvh::new(&shapes);
+        Self {
+            shapes,
+            lights,
+            max_depth: World::DEFAULT_MAX_DEPTH,
+            bvh,
+        }
+    }
+
+    pub fn shapes(&self) -> &[&'a Shape] {
+        &self.shapes
     }
 
+    /// The colour seen along `ray`, sampling area lights from a fixed seed so that
+    /// the result is repeatable.
     pub fn colour_at(&self, ray: Ray) -> Colour {
+        self.colour_at_with_rng(ray, &mut StdRng::seed_from_u64(0))
+    }
+
+    /// The colour seen along `ray`, drawing area light samples from `rng`.
+    pub fn colour_at_with_rng(&self, ray: Ray, rng: &mut impl Rng) -> Colour {
+        self.colour_at_depth(ray, self.max_depth, rng)
+    }
+
+    fn colour_at_depth(&self, ray: Ray, remaining: usize, rng: &mut impl Rng) -> Colour {
         let intersections = self.intersect(ray);
-        find_hit(&intersections).map_or(Colour::new(0.0, 0.0, 0.0), |hit| {
-            self.shade_hit(&hit.prepare_computations(ray))
-        })
+        match find_hit(&intersections) {
+            Some(hit) => self.shade_hit(
+                &hit.prepare_computations(ray, &intersections),
+                remaining,
+                rng,
+            ),
+            None => Colour::new(0.0, 0.0, 0.0),
+        }
     }
 
     fn intersect(&self, ray: Ray) -> Vec<Intersection<'a>> {
-        self.shapes
-            .iter()
-            .flat_map(|shape| shape.intersect(&ray))
-            .collect()
+        let mut intersections = self.bvh.intersect(&ray);
+        intersections.sort_by(|a, b| a.t.total_cmp(&b.t));
+        intersections
+    }
+
+    pub fn shade_hit(&self, comps: &Computations, remaining: usize, rng: &mut impl Rng) -> Colour {
+        let surface = self.surface_colour(comps, rng);
+        let reflected = self.reflected_colour(comps, remaining, rng);
+        let refracted = self.refracted_colour(comps, remaining, rng);
+
+        let material = comps.shape.get_material();
+        if material.reflective > 0.0 && material.transparency > 0.0 {
+            let reflectance = comps.schlick();
+            &(&surface + &(&reflected * reflectance)) + &(&refracted * (1.0 - reflectance))
+        } else {
+            &(&surface + &reflected) + &refracted
+        }
     }
 
-    pub fn shade_hit(&self, comps: &Computations) -> Colour {
-        lighting(
+    // Each light is sampled once, and only the samples that reach the surface are shaded,
+    // so the shading and the shadow agree on where the light is.
+    fn surface_colour(&self, comps: &Computations, rng: &mut impl Rng) -> Colour {
+        let material = comps.shape.get_material();
+        let mut colour = ambient(
             comps.shape,
-            comps.shape.get_material(),
-            &self.light,
+            material,
+            &self.ambient_intensity(),
             &comps.over_point,
-            &comps.eye_v,
-            &comps.normal_v,
-            self.is_shadowed(comps.over_point),
-        )
+        );
+
+        for light in &self.lights {
+            if light.falloff_at(&comps.over_point) == 0.0 {
+                continue;
+            }
+
+            let samples = light.samples_from(&comps.over_point, rng);
+            let visible = self.visible_samples(comps.over_point, &samples);
+            if visible.is_empty() {
+                continue;
+            }
+
+            let direct = direct_lighting(
+                comps.shape,
+                material,
+                light,
+                &comps.over_point,
+                &comps.eye_v,
+                &comps.normal_v,
+                &visible,
+            );
+            colour = &colour + &(&direct * (visible.len() as f64 / samples.len() as f64));
+        }
+        colour
+    }
+
+    // The ambient term stands in for indirect light, so it is applied once using the
+    // average intensity of the lights rather than once per light.
+    fn ambient_intensity(&self) -> Colour {
+        if self.lights.is_empty() {
+            return Colour::new(0.0, 0.0, 0.0);
+        }
+
+        let total = self
+            .lights
+            .iter()
+            .fold(Colour::new(0.0, 0.0, 0.0), |total, light| {
+                &total + &light.intensity()
+            });
+        &total * (1.0 / self.lights.len() as f64)
+    }
+
+    pub fn reflected_colour(
+        &self,
+        comps: &Computations,
+        remaining: usize,
+        rng: &mut impl Rng,
+    ) -> Colour {
+        let reflective = comps.shape.get_material().reflective;
+        if remaining == 0 || reflective == 0.0 {
+            return Colour::new(0.0, 0.0, 0.0);
+        }
+
+        let reflect_ray = Ray::new(comps.over_point, comps.reflect_v);
+        &self.colour_at_depth(reflect_ray, remaining - 1, rng) * reflective
+    }
+
+    pub fn refracted_colour(
+        &self,
+        comps: &Computations,
+        remaining: usize,
+        rng: &mut impl Rng,
+    ) -> Colour {
+        let transparency = comps.shape.get_material().transparency;
+        if remaining == 0 || transparency == 0.0 {
+            return Colour::new(0.0, 0.0, 0.0);
+        }
+
+        let n_ratio = comps.n1 / comps.n2;
+        let cos_i = comps.eye_v.dot(&comps.normal_v);
+        let sin2_t = n_ratio.powi(2) * (1.0 - cos_i.powi(2));
+        if sin2_t > 1.0 {
+            return Colour::new(0.0, 0.0, 0.0);
+        }
+
+        let cos_t = (1.0 - sin2_t).sqrt();
+        let direction = &(&comps.normal_v * (n_ratio * cos_i - cos_t)) - &(&comps.eye_v * n_ratio);
+        let refract_ray = Ray::new(comps.under_point, direction);
+
+        &self.colour_at_depth(refract_ray, remaining - 1, rng) * transparency
+    }
+
+    /// The fraction of `light`'s samples visible from `point`.
+    pub fn intensity_at(&self, light: &Light, point: Point, rng: &mut impl Rng) -> f64 {
+        if light.falloff_at(&point) == 0.0 {
+            return 0.0;
+        }
+
+        let samples = light.samples_from(&point, rng);
+        self.visible_samples(point, &samples).len() as f64 / samples.len() as f64
     }
 
-    fn is_shadowed(&self, point: Point) -> bool {
-        let v = &self.light.position - &point;
-        let distance = v.magnitude();
-        let direction = v.normalize();
+    fn visible_samples(&self, point: Point, samples: &[LightSample]) -> Vec<LightSample> {
+        samples
+            .iter()
+            .filter(|sample| !self.is_shadowed(point, sample))
+            .copied()
+            .collect()
+    }
 
-        let ray = Ray::new(point, direction);
+    fn is_shadowed(&self, point: Point, sample: &LightSample) -> bool {
+        let ray = Ray::new(point, sample.direction);
         let intersections = self.intersect(ray);
         let hit = find_hit(&intersections);
 
-        hit.map_or(false, |hit| hit.t < distance)
+        hit.is_some_and(|hit| hit.t < sample.distance)
     }
 }
 
 #[cfg(test)]
 mod tests {
 
-    use crate::{PointLight, World};
+    use crate::{ambient, direct_lighting, AreaLight, DirectionalLight, PointLight, World};
     use core::{Colour, Point, Vector};
     use math::{Matrix4, Transform};
-    use shapes::{Material, Patn, Pattern, Sphere};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use shapes::{Cube, Material, Patn, Pattern, Plane, Sphere};
+
+    fn rng() -> StdRng {
+        StdRng::seed_from_u64(0)
+    }
+
+    fn outer_material() -> Material {
+        let mut material = Material::new(Pattern::new(
+            Patn::Solid(Colour::new(0.8, 1.0, 0.6)),
+            Transform::default().build(),
+        ));
+        material.diffuse = 0.7;
+        material.specular = 0.2;
+        material
+    }
 
     mod contruction {
         use super::*;
@@ -66,10 +223,10 @@ mod tests {
         #[test]
         fn empty() {
             let light = PointLight::new(Point::new(-10.0, 10.0, -10.0), Colour::new(1.0, 1.0, 1.0));
-            let world = World::new(vec![], light);
+            let world = World::new(vec![], vec![light]);
 
-            assert_eq!(world.light, light);
-            assert_eq!(world.shapes.len(), 0);
+            assert_eq!(world.lights, vec![light]);
+            assert_eq!(world.shapes().len(), 0);
         }
 
         #[test]
@@ -81,9 +238,9 @@ mod tests {
                 Material::default(),
             );
 
-            let world = World::new(vec![&s1, &s2], light);
-            assert_eq!(world.light, light);
-            assert_eq!(world.shapes.len(), 2);
+            let world = World::new(vec![&s1, &s2], vec![light]);
+            assert_eq!(world.lights, vec![light]);
+            assert_eq!(world.shapes().len(), 2);
         }
     }
 
@@ -102,7 +259,7 @@ mod tests {
             );
 
             let light = PointLight::new(Point::new(-10.0, 10.0, -10.0), Colour::new(1.0, 1.0, 1.0));
-            let world = World::new(vec![&s1, &s2], light);
+            let world = World::new(vec![&s1, &s2], vec![light]);
 
             let intersections = world.intersect(Ray::new(
                 Point::new(0.0, 0.0, -5.0),
@@ -133,7 +290,7 @@ mod tests {
             );
 
             let light = PointLight::new(Point::new(-10.0, 10.0, -10.0), Colour::new(1.0, 1.0, 1.0));
-            let world = World::new(vec![&s1, &s2], light);
+            let world = World::new(vec![&s1, &s2], vec![light]);
 
             let colour = world.colour_at(Ray::new(
                 Point::new(0.0, 0.0, -5.0),
@@ -157,7 +314,7 @@ mod tests {
             );
 
             let light = PointLight::new(Point::new(0.0, 0.25, 0.0), Colour::new(1.0, 1.0, 1.0));
-            let world = World::new(vec![&s1, &s2], light);
+            let world = World::new(vec![&s1, &s2], vec![light]);
 
             let colour = world.colour_at(Ray::new(
                 Point::new(0.0, 0.0, 0.0),
@@ -174,7 +331,7 @@ mod tests {
                 Transform::default().scaling(0.5, 0.5, 0.5).build(),
                 Material::default(),
             );
-            let world = World::new(vec![&s1, &s2], light);
+            let world = World::new(vec![&s1, &s2], vec![light]);
 
             let ray = math::Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 1.0, 0.0));
 
@@ -196,7 +353,7 @@ mod tests {
             );
 
             let light = PointLight::new(Point::new(-10.0, 10.0, -10.0), Colour::new(1.0, 1.0, 1.0));
-            let world = World::new(vec![&s1, &s2], light);
+            let world = World::new(vec![&s1, &s2], vec![light]);
 
             let ray = math::Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
 
@@ -214,8 +371,10 @@ mod tests {
             material_outer.specular = 0.2;
             let s1 = Sphere::new(Matrix4::identity(), material_outer);
 
-            let mut material_inner = Material::default();
-            material_inner.ambient = 1.0;
+            let material_inner = Material {
+                ambient: 1.0,
+                ..Default::default()
+            };
 
             let s2 = Sphere::new(
                 Transform::default().scaling(0.5, 0.5, 0.5).build(),
@@ -223,7 +382,7 @@ mod tests {
             );
 
             let light = PointLight::new(Point::new(-10.0, 10.0, -10.0), Colour::new(1.0, 1.0, 1.0));
-            let world = World::new(vec![&s1, &s2], light);
+            let world = World::new(vec![&s1, &s2], vec![light]);
 
             let ray = math::Ray::new(Point::new(0.0, 0.0, 0.75), Vector::new(0.0, 0.0, -1.0));
 
@@ -249,10 +408,10 @@ mod tests {
             );
 
             let light = PointLight::new(Point::new(-10.0, 10.0, -10.0), Colour::new(1.0, 1.0, 1.0));
-            let world = World::new(vec![&s1, &s2], light);
+            let world = World::new(vec![&s1, &s2], vec![light]);
             let point = Point::new(0.0, 10.0, 0.0);
 
-            assert!(!world.is_shadowed(point));
+            assert_eq!(world.intensity_at(&light, point, &mut rng()), 1.0);
         }
 
         #[test]
@@ -270,10 +429,10 @@ mod tests {
             );
 
             let light = PointLight::new(Point::new(-10.0, 10.0, -10.0), Colour::new(1.0, 1.0, 1.0));
-            let world = World::new(vec![&s1, &s2], light);
+            let world = World::new(vec![&s1, &s2], vec![light]);
             let point = Point::new(10.0, -10.0, 10.0);
 
-            assert!(world.is_shadowed(point));
+            assert_eq!(world.intensity_at(&light, point, &mut rng()), 0.0);
         }
 
         #[test]
@@ -291,10 +450,10 @@ mod tests {
             );
 
             let light = PointLight::new(Point::new(-10.0, 10.0, -10.0), Colour::new(1.0, 1.0, 1.0));
-            let world = World::new(vec![&s1, &s2], light);
+            let world = World::new(vec![&s1, &s2], vec![light]);
             let point = Point::new(-20.0, 20.0, -20.0);
 
-            assert!(!world.is_shadowed(point));
+            assert_eq!(world.intensity_at(&light, point, &mut rng()), 1.0);
         }
 
         #[test]
@@ -312,10 +471,77 @@ mod tests {
             );
 
             let light = PointLight::new(Point::new(-10.0, 10.0, -10.0), Colour::new(1.0, 1.0, 1.0));
-            let world = World::new(vec![&s1, &s2], light);
+            let world = World::new(vec![&s1, &s2], vec![light]);
             let point = Point::new(-2.0, 2.0, -2.0);
 
-            assert!(!world.is_shadowed(point));
+            assert_eq!(world.intensity_at(&light, point, &mut rng()), 1.0);
+        }
+
+        #[test]
+        fn intensity_at_point_light() {
+            let s1 = Sphere::new(Matrix4::identity(), Material::default());
+            let light = PointLight::new(Point::new(-10.0, 10.0, -10.0), Colour::new(1.0, 1.0, 1.0));
+            let world = World::new(vec![&s1], vec![light]);
+
+            assert_eq!(
+                world.intensity_at(&light, Point::new(0.0, 10.0, 0.0), &mut rng()),
+                1.0
+            );
+            assert_eq!(
+                world.intensity_at(&light, Point::new(10.0, -10.0, 10.0), &mut rng()),
+                0.0
+            );
+        }
+
+        #[test]
+        fn directional_shadow_rays_extend_to_infinity() {
+            let blocker = Sphere::new(
+                Transform::default().translation(0.0, 1000.0, 0.0).build(),
+                Material::default(),
+            );
+            let light =
+                DirectionalLight::new(Vector::new(0.0, -1.0, 0.0), Colour::new(1.0, 1.0, 1.0));
+            let world = World::new(vec![&blocker], vec![light]);
+
+            assert_eq!(
+                world.intensity_at(&light, Point::new(0.0, 0.0, 0.0), &mut rng()),
+                0.0
+            );
+            assert_eq!(
+                world.intensity_at(&light, Point::new(2.0, 0.0, 0.0), &mut rng()),
+                1.0
+            );
+        }
+
+        #[test]
+        fn intensity_at_partially_occluded_area_light() {
+            // A slab covering x < 0 halfway between the point and the light hides
+            // exactly the two left-hand columns of the 4x2 grid.
+            let blocker = Cube::new(
+                Transform::default()
+                    .scaling(50.0, 1.0, 50.0)
+                    .translation(-50.0, 5.0, 0.0)
+                    .build(),
+                Material::default(),
+            );
+            let light = AreaLight::new(
+                Point::new(-1.0, 10.0, -0.5),
+                Vector::new(2.0, 0.0, 0.0),
+                4,
+                Vector::new(0.0, 0.0, 1.0),
+                2,
+                Colour::new(1.0, 1.0, 1.0),
+            );
+            let world = World::new(vec![&blocker], vec![light]);
+
+            assert_eq!(
+                world.intensity_at(&light, Point::new(0.0, 0.0, 0.0), &mut rng()),
+                0.5
+            );
+            assert_eq!(
+                world.intensity_at(&light, Point::new(0.0, 20.0, 0.0), &mut rng()),
+                1.0
+            );
         }
     }
 
@@ -324,6 +550,56 @@ mod tests {
 
         use super::*;
 
+        #[test]
+        fn shading_and_shadows_share_area_light_samples() {
+            // The same slab as above hides the left half of the light from the floor.
+            let blocker = Cube::new(
+                Transform::default()
+                    .scaling(50.0, 1.0, 50.0)
+                    .translation(-50.0, 5.0, 0.0)
+                    .build(),
+                Material::default(),
+            );
+            let floor = Plane::new(Matrix4::identity(), Material::default());
+            let light = AreaLight::new(
+                Point::new(-1.0, 10.0, -0.5),
+                Vector::new(2.0, 0.0, 0.0),
+                4,
+                Vector::new(0.0, 0.0, 1.0),
+                2,
+                Colour::new(1.0, 1.0, 1.0),
+            );
+            let world = World::new(vec![&blocker, &floor], vec![light]);
+
+            let ray = math::Ray::new(Point::new(0.0, 1.0, 0.0), Vector::new(0.0, -1.0, 0.0));
+            let intersection = Intersection::new(1.0, &floor);
+            let comps = intersection.prepare_computations(ray, &[intersection]);
+
+            let visible: Vec<_> = light
+                .samples_from(&comps.over_point, &mut rng())
+                .into_iter()
+                .filter(|sample| sample.direction.x() >= 0.0)
+                .collect();
+            let material = floor.get_material();
+            let expected = &ambient(
+                &floor,
+                material,
+                &world.ambient_intensity(),
+                &comps.over_point,
+            ) + &(&direct_lighting(
+                &floor,
+                material,
+                &light,
+                &comps.over_point,
+                &comps.eye_v,
+                &comps.normal_v,
+                &visible,
+            ) * 0.5);
+
+            assert_eq!(visible.len(), 4);
+            assert_eq!(world.shade_hit(&comps, 0, &mut rng()), expected);
+        }
+
         #[test]
         fn intersection_in_shadow() {
             let s1 = Sphere::new(Matrix4::identity(), Material::default());
@@ -333,17 +609,84 @@ mod tests {
             );
 
             let light = PointLight::new(Point::new(0.0, 0.0, -10.0), Colour::new(1.0, 1.0, 1.0));
-            let world = World::new(vec![&s1, &s2], light);
+            let world = World::new(vec![&s1, &s2], vec![light]);
 
             let ray = math::Ray::new(Point::new(0.0, 0.0, 5.0), Vector::new(0.0, 0.0, 1.0));
             let intersection = Intersection::new(4.0, &s2);
 
             assert_eq!(
-                world.shade_hit(&intersection.prepare_computations(ray)),
+                world.shade_hit(
+                    &intersection.prepare_computations(ray, &[intersection]),
+                    world.max_depth,
+                    &mut rng()
+                ),
                 Colour::new(0.1, 0.1, 0.1)
             );
         }
 
+        #[test]
+        fn contributions_from_each_light_are_summed() {
+            let sphere = Sphere::new(Matrix4::identity(), Material::default());
+            let light = PointLight::new(Point::new(0.0, 0.0, -10.0), Colour::new(1.0, 1.0, 1.0));
+            let world = World::new(vec![&sphere], vec![light, light]);
+
+            let ray = math::Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+            let intersection = Intersection::new(4.0, &sphere);
+
+            // Ambient 0.1 applied once, diffuse 0.9 and specular 0.9 per light.
+            assert_eq!(
+                world.shade_hit(
+                    &intersection.prepare_computations(ray, &[intersection]),
+                    world.max_depth,
+                    &mut rng()
+                ),
+                Colour::new(3.7, 3.7, 3.7)
+            );
+        }
+
+        #[test]
+        fn each_light_is_shadowed_separately() {
+            let s1 = Sphere::new(Matrix4::identity(), Material::default());
+            let s2 = Sphere::new(
+                Transform::default().translation(0.0, 0.0, -5.0).build(),
+                Material::default(),
+            );
+
+            let visible = PointLight::new(Point::new(0.0, 0.0, -3.0), Colour::new(1.0, 1.0, 1.0));
+            let blocked = PointLight::new(Point::new(0.0, 0.0, -10.0), Colour::new(1.0, 1.0, 1.0));
+            let world = World::new(vec![&s1, &s2], vec![visible, blocked]);
+
+            let ray = math::Ray::new(Point::new(0.0, 0.0, -2.0), Vector::new(0.0, 0.0, 1.0));
+            let intersection = Intersection::new(1.0, &s1);
+
+            assert_eq!(
+                world.shade_hit(
+                    &intersection.prepare_computations(ray, &[intersection]),
+                    world.max_depth,
+                    &mut rng()
+                ),
+                Colour::new(1.9, 1.9, 1.9)
+            );
+        }
+
+        #[test]
+        fn no_lights_gives_black() {
+            let sphere = Sphere::new(Matrix4::identity(), Material::default());
+            let world = World::new(vec![&sphere], vec![]);
+
+            let ray = math::Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+            let intersection = Intersection::new(4.0, &sphere);
+
+            assert_eq!(
+                world.shade_hit(
+                    &intersection.prepare_computations(ray, &[intersection]),
+                    world.max_depth,
+                    &mut rng()
+                ),
+                Colour::new(0.0, 0.0, 0.0)
+            );
+        }
+
         // #[test]
         // fn hit_should_offset_point() {
         //     let s1 = Sphere::new(
@@ -352,15 +695,362 @@ mod tests {
         //     );
 
         //     let light = PointLight::new(Point::new(0.0, 0.0, -10.0), Colour::new(1.0, 1.0, 1.0));
-        //     let world = World::new(vec![&s1], light);
+        //     let world = World::new(vec![&s1], vec![light]);
 
         //     let ray = math::Ray::new(Point::new(0.0, 0.0, 5.0), Vector::new(0.0, 0.0, 1.0));
         //     let intersection = Intersection::new(5.0, &s1);
 
         //     assert_eq!(
-        //         world.shade_hit(&intersection.prepare_computations(ray)),
+        //         world.shade_hit(&intersection.prepare_computations(ray, &[intersection]), world.max_depth),
         //         Colour::new(0.1, 0.1, 0.1)
         //     );
         // }
     }
+
+    mod reflection {
+        use super::*;
+        use shapes::Intersection;
+        use std::f64::consts::FRAC_1_SQRT_2;
+
+        #[test]
+        fn nonreflective_material() {
+            let s1 = Sphere::new(Matrix4::identity(), outer_material());
+            let inner_material = Material {
+                ambient: 1.0,
+                ..Default::default()
+            };
+            let s2 = Sphere::new(
+                Transform::default().scaling(0.5, 0.5, 0.5).build(),
+                inner_material,
+            );
+
+            let light = PointLight::new(Point::new(-10.0, 10.0, -10.0), Colour::new(1.0, 1.0, 1.0));
+            let world = World::new(vec![&s1, &s2], vec![light]);
+
+            let ray = math::Ray::new(Point::new(0.0, 0.0, 0.0), Vector::new(0.0, 0.0, 1.0));
+            let intersection = Intersection::new(1.0, &s2);
+
+            assert_eq!(
+                world.reflected_colour(
+                    &intersection.prepare_computations(ray, &[intersection]),
+                    world.max_depth,
+                    &mut rng()
+                ),
+                Colour::new(0.0, 0.0, 0.0)
+            );
+        }
+
+        #[test]
+        fn reflective_material() {
+            let s1 = Sphere::new(Matrix4::identity(), outer_material());
+            let s2 = Sphere::new(
+                Transform::default().scaling(0.5, 0.5, 0.5).build(),
+                Material::default(),
+            );
+            let plane_material = Material {
+                reflective: 0.5,
+                ..Default::default()
+            };
+            let plane = Plane::new(
+                Transform::default().translation(0.0, -1.0, 0.0).build(),
+                plane_material,
+            );
+
+            let light = PointLight::new(Point::new(-10.0, 10.0, -10.0), Colour::new(1.0, 1.0, 1.0));
+            let world = World::new(vec![&s1, &s2, &plane], vec![light]);
+
+            let ray = math::Ray::new(
+                Point::new(0.0, 0.0, -3.0),
+                Vector::new(0.0, -FRAC_1_SQRT_2, FRAC_1_SQRT_2),
+            );
+            let intersection = Intersection::new(2.0_f64.sqrt(), &plane);
+
+            assert_eq!(
+                world.reflected_colour(
+                    &intersection.prepare_computations(ray, &[intersection]),
+                    world.max_depth,
+                    &mut rng()
+                ),
+                Colour::new(0.19035, 0.23793, 0.14276)
+            );
+        }
+
+        #[test]
+        fn shade_hit_with_reflective_material() {
+            let s1 = Sphere::new(Matrix4::identity(), outer_material());
+            let s2 = Sphere::new(
+                Transform::default().scaling(0.5, 0.5, 0.5).build(),
+                Material::default(),
+            );
+            let plane_material = Material {
+                reflective: 0.5,
+                ..Default::default()
+            };
+            let plane = Plane::new(
+                Transform::default().translation(0.0, -1.0, 0.0).build(),
+                plane_material,
+            );
+
+            let light = PointLight::new(Point::new(-10.0, 10.0, -10.0), Colour::new(1.0, 1.0, 1.0));
+            let world = World::new(vec![&s1, &s2, &plane], vec![light]);
+
+            let ray = math::Ray::new(
+                Point::new(0.0, 0.0, -3.0),
+                Vector::new(0.0, -FRAC_1_SQRT_2, FRAC_1_SQRT_2),
+            );
+            let intersection = Intersection::new(2.0_f64.sqrt(), &plane);
+
+            assert_eq!(
+                world.shade_hit(
+                    &intersection.prepare_computations(ray, &[intersection]),
+                    world.max_depth,
+                    &mut rng()
+                ),
+                Colour::new(0.87677, 0.92436, 0.82918)
+            );
+        }
+
+        #[test]
+        fn mutually_reflective_surfaces_terminate() {
+            let mirror = Material {
+                reflective: 1.0,
+                ..Default::default()
+            };
+            let lower = Plane::new(
+                Transform::default().translation(0.0, -1.0, 0.0).build(),
+                mirror,
+            );
+            let upper = Plane::new(
+                Transform::default()
+                    .rotation_x(std::f64::consts::PI)
+                    .translation(0.0, 1.0, 0.0)
+                    .build(),
+                mirror,
+            );
+
+            let light = PointLight::new(Point::new(0.0, 0.0, 0.0), Colour::new(1.0, 1.0, 1.0));
+            let mut world = World::new(vec![&lower, &upper], vec![light]);
+            let ray = math::Ray::new(Point::new(0.0, 0.0, 0.0), Vector::new(0.0, 1.0, 0.0));
+
+            // Each bounce lands facing the light, adding ambient 0.1, diffuse 0.9 and
+            // specular 0.9, until the remaining depth runs out.
+            for (max_depth, expected) in [(0, 1.9), (2, 5.7), (5, 11.4)] {
+                world.max_depth = max_depth;
+                assert_eq!(
+                    world.colour_at(ray),
+                    Colour::new(expected, expected, expected)
+                );
+            }
+        }
+
+        #[test]
+        fn reflected_colour_at_maximum_recursive_depth() {
+            let s1 = Sphere::new(Matrix4::identity(), outer_material());
+            let s2 = Sphere::new(
+                Transform::default().scaling(0.5, 0.5, 0.5).build(),
+                Material::default(),
+            );
+            let plane_material = Material {
+                reflective: 0.5,
+                ..Default::default()
+            };
+            let plane = Plane::new(
+                Transform::default().translation(0.0, -1.0, 0.0).build(),
+                plane_material,
+            );
+
+            let light = PointLight::new(Point::new(-10.0, 10.0, -10.0), Colour::new(1.0, 1.0, 1.0));
+            let world = World::new(vec![&s1, &s2, &plane], vec![light]);
+
+            let ray = math::Ray::new(
+                Point::new(0.0, 0.0, -3.0),
+                Vector::new(0.0, -FRAC_1_SQRT_2, FRAC_1_SQRT_2),
+            );
+            let intersection = Intersection::new(2.0_f64.sqrt(), &plane);
+
+            assert_eq!(
+                world.reflected_colour(
+                    &intersection.prepare_computations(ray, &[intersection]),
+                    0,
+                    &mut rng()
+                ),
+                Colour::new(0.0, 0.0, 0.0)
+            );
+        }
+    }
+
+    mod refraction {
+        use super::*;
+        use shapes::Intersection;
+        use std::f64::consts::FRAC_1_SQRT_2;
+
+        #[test]
+        fn opaque_surface() {
+            let s1 = Sphere::new(Matrix4::identity(), outer_material());
+            let s2 = Sphere::new(
+                Transform::default().scaling(0.5, 0.5, 0.5).build(),
+                Material::default(),
+            );
+
+            let light = PointLight::new(Point::new(-10.0, 10.0, -10.0), Colour::new(1.0, 1.0, 1.0));
+            let world = World::new(vec![&s1, &s2], vec![light]);
+
+            let ray = math::Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+            let xs = vec![Intersection::new(4.0, &s1), Intersection::new(6.0, &s1)];
+
+            assert_eq!(
+                world.refracted_colour(
+                    &xs[0].prepare_computations(ray, &xs),
+                    world.max_depth,
+                    &mut rng()
+                ),
+                Colour::new(0.0, 0.0, 0.0)
+            );
+        }
+
+        #[test]
+        fn refracted_colour_at_maximum_recursive_depth() {
+            let mut material = outer_material();
+            material.transparency = 1.0;
+            material.refractive_index = 1.5;
+            let s1 = Sphere::new(Matrix4::identity(), material);
+            let s2 = Sphere::new(
+                Transform::default().scaling(0.5, 0.5, 0.5).build(),
+                Material::default(),
+            );
+
+            let light = PointLight::new(Point::new(-10.0, 10.0, -10.0), Colour::new(1.0, 1.0, 1.0));
+            let world = World::new(vec![&s1, &s2], vec![light]);
+
+            let ray = math::Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+            let xs = vec![Intersection::new(4.0, &s1), Intersection::new(6.0, &s1)];
+
+            assert_eq!(
+                world.refracted_colour(&xs[0].prepare_computations(ray, &xs), 0, &mut rng()),
+                Colour::new(0.0, 0.0, 0.0)
+            );
+        }
+
+        #[test]
+        fn total_internal_reflection() {
+            let mut material = outer_material();
+            material.transparency = 1.0;
+            material.refractive_index = 1.5;
+            let s1 = Sphere::new(Matrix4::identity(), material);
+            let s2 = Sphere::new(
+                Transform::default().scaling(0.5, 0.5, 0.5).build(),
+                Material::default(),
+            );
+
+            let light = PointLight::new(Point::new(-10.0, 10.0, -10.0), Colour::new(1.0, 1.0, 1.0));
+            let world = World::new(vec![&s1, &s2], vec![light]);
+
+            let ray = math::Ray::new(
+                Point::new(0.0, 0.0, FRAC_1_SQRT_2),
+                Vector::new(0.0, 1.0, 0.0),
+            );
+            let xs = vec![
+                Intersection::new(-FRAC_1_SQRT_2, &s1),
+                Intersection::new(FRAC_1_SQRT_2, &s1),
+            ];
+
+            assert_eq!(
+                world.refracted_colour(
+                    &xs[1].prepare_computations(ray, &xs),
+                    world.max_depth,
+                    &mut rng()
+                ),
+                Colour::new(0.0, 0.0, 0.0)
+            );
+        }
+
+        #[test]
+        fn shade_hit_with_transparent_material() {
+            let s1 = Sphere::new(Matrix4::identity(), outer_material());
+            let s2 = Sphere::new(
+                Transform::default().scaling(0.5, 0.5, 0.5).build(),
+                Material::default(),
+            );
+            let floor = Plane::new(
+                Transform::default().translation(0.0, -1.0, 0.0).build(),
+                Material {
+                    transparency: 0.5,
+                    refractive_index: 1.5,
+                    ..Default::default()
+                },
+            );
+            let mut ball_material = Material::new(Pattern::new(
+                Patn::Solid(Colour::new(1.0, 0.0, 0.0)),
+                Transform::default().build(),
+            ));
+            ball_material.ambient = 0.5;
+            let ball = Sphere::new(
+                Transform::default().translation(0.0, -3.5, -0.5).build(),
+                ball_material,
+            );
+
+            let light = PointLight::new(Point::new(-10.0, 10.0, -10.0), Colour::new(1.0, 1.0, 1.0));
+            let world = World::new(vec![&s1, &s2, &floor, &ball], vec![light]);
+
+            let ray = math::Ray::new(
+                Point::new(0.0, 0.0, -3.0),
+                Vector::new(0.0, -FRAC_1_SQRT_2, FRAC_1_SQRT_2),
+            );
+            let xs = vec![Intersection::new(2.0_f64.sqrt(), &floor)];
+
+            assert_eq!(
+                world.shade_hit(
+                    &xs[0].prepare_computations(ray, &xs),
+                    world.max_depth,
+                    &mut rng()
+                ),
+                Colour::new(0.93642, 0.68642, 0.68642)
+            );
+        }
+
+        #[test]
+        fn shade_hit_with_reflective_transparent_material() {
+            let s1 = Sphere::new(Matrix4::identity(), outer_material());
+            let s2 = Sphere::new(
+                Transform::default().scaling(0.5, 0.5, 0.5).build(),
+                Material::default(),
+            );
+            let floor = Plane::new(
+                Transform::default().translation(0.0, -1.0, 0.0).build(),
+                Material {
+                    reflective: 0.5,
+                    transparency: 0.5,
+                    refractive_index: 1.5,
+                    ..Default::default()
+                },
+            );
+            let mut ball_material = Material::new(Pattern::new(
+                Patn::Solid(Colour::new(1.0, 0.0, 0.0)),
+                Transform::default().build(),
+            ));
+            ball_material.ambient = 0.5;
+            let ball = Sphere::new(
+                Transform::default().translation(0.0, -3.5, -0.5).build(),
+                ball_material,
+            );
+
+            let light = PointLight::new(Point::new(-10.0, 10.0, -10.0), Colour::new(1.0, 1.0, 1.0));
+            let world = World::new(vec![&s1, &s2, &floor, &ball], vec![light]);
+
+            let ray = math::Ray::new(
+                Point::new(0.0, 0.0, -3.0),
+                Vector::new(0.0, -FRAC_1_SQRT_2, FRAC_1_SQRT_2),
+            );
+            let xs = vec![Intersection::new(2.0_f64.sqrt(), &floor)];
+
+            assert_eq!(
+                world.shade_hit(
+                    &xs[0].prepare_computations(ray, &xs),
+                    world.max_depth,
+                    &mut rng()
+                ),
+                Colour::new(0.93391, 0.69643, 0.69243)
+            );
+        }
+    }
 }
@@ -0,0 +1,271 @@
+use core::Point;
+use math::{Matrix4, Ray};
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct BoundingBox {
+    pub min: Point,
+    pub max: Point,
+}
+
+impl BoundingBox {
+    pub fn new(min: Point, max: Point) -> Self {
+        Self { min, max }
+    }
+
+    pub fn empty() -> Self {
+        Self::new(
+            Point::new(f64::INFINITY, f64::INFINITY, f64::INFINITY),
+            Point::new(f64::NEG_INFINITY, f64::NEG_INFINITY, f64::NEG_INFINITY),
+        )
+    }
+
+    pub fn is_finite(&self) -> bool {
+        [self.min, self.max]
+            .iter()
+            .all(|p| p.x().is_finite() && p.y().is_finite() && p.z().is_finite())
+    }
+
+    /// Whether the box holds no points at all, as `empty` does until points are added.
+    pub fn is_empty(&self) -> bool {
+        self.min.x() > self.max.x() || self.min.y() > self.max.y() || self.min.z() > self.max.z()
+    }
+
+    pub fn add_point(&self, point: &Point) -> Self {
+        Self::new(
+            Point::new(
+                self.min.x().min(point.x()),
+                self.min.y().min(point.y()),
+                self.min.z().min(point.z()),
+            ),
+            Point::new(
+                self.max.x().max(point.x()),
+                self.max.y().max(point.y()),
+                self.max.z().max(point.z()),
+            ),
+        )
+    }
+
+    pub fn merge(&self, other: &BoundingBox) -> Self {
+        self.add_point(&other.min).add_point(&other.max)
+    }
+
+    pub fn centroid(&self) -> Point {
+        Point::new(
+            (self.min.x() + self.max.x()) / 2.0,
+            (self.min.y() + self.max.y()) / 2.0,
+            (self.min.z() + self.max.z()) / 2.0,
+        )
+    }
+
+    pub fn transform(&self, matrix: &Matrix4) -> Self {
+        let (min, max) = (self.min, self.max);
+        let corners = [
+            [min.x(), min.y(), min.z()],
+            [min.x(), min.y(), max.z()],
+            [min.x(), max.y(), min.z()],
+            [min.x(), max.y(), max.z()],
+            [max.x(), min.y(), min.z()],
+            [max.x(), min.y(), max.z()],
+            [max.x(), max.y(), min.z()],
+            [max.x(), max.y(), max.z()],
+        ];
+
+        corners.iter().fold(BoundingBox::empty(), |bounds, corner| {
+            let [x, y, z] = [0, 1, 2].map(|row| transform_axis(matrix, row, corner));
+            // Opposing infinities leave an axis undefined, so it becomes unbounded.
+            let lower = |v: f64| if v.is_nan() { f64::NEG_INFINITY } else { v };
+            let upper = |v: f64| if v.is_nan() { f64::INFINITY } else { v };
+            bounds
+                .add_point(&Point::new(lower(x), lower(y), lower(z)))
+                .add_point(&Point::new(upper(x), upper(y), upper(z)))
+        })
+    }
+
+    pub fn intersects(&self, ray: &Ray) -> bool {
+        if self.is_empty() {
+            return false;
+        }
+
+        let (x_tmin, x_tmax) = check_axis(
+            ray.origin.x(),
+            ray.direction.x(),
+            self.min.x(),
+            self.max.x(),
+        );
+        let (y_tmin, y_tmax) = check_axis(
+            ray.origin.y(),
+            ray.direction.y(),
+            self.min.y(),
+            self.max.y(),
+        );
+        let (z_tmin, z_tmax) = check_axis(
+            ray.origin.z(),
+            ray.direction.z(),
+            self.min.z(),
+            self.max.z(),
+        );
+
+        let tmin = x_tmin.max(y_tmin).max(z_tmin);
+        let tmax = x_tmax.min(y_tmax).min(z_tmax);
+
+        tmin <= tmax && tmax >= 0.0
+    }
+}
+
+fn transform_axis(matrix: &Matrix4, row: usize, corner: &[f64; 3]) -> f64 {
+    // Zero entries are skipped so an infinite extent never turns into 0 * inf = NaN.
+    corner
+        .iter()
+        .enumerate()
+        .filter(|(column, _)| matrix[row][*column] != 0.0)
+        .map(|(column, value)| matrix[row][column] * value)
+        .sum::<f64>()
+        + matrix[row][3]
+}
+
+fn check_axis(origin: f64, direction: f64, min: f64, max: f64) -> (f64, f64) {
+    if direction == 0.0 {
+        return if origin < min || origin > max {
+            (f64::INFINITY, f64::NEG_INFINITY)
+        } else {
+            (f64::NEG_INFINITY, f64::INFINITY)
+        };
+    }
+
+    let t1 = (min - origin) / direction;
+    let t2 = (max - origin) / direction;
+
+    if t1 > t2 {
+        (t2, t1)
+    } else {
+        (t1, t2)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::BoundingBox;
+    use core::{Point, Vector};
+    use math::{Ray, Transform};
+
+    mod construction {
+        use super::*;
+
+        #[test]
+        fn empty_box() {
+            let b = BoundingBox::empty();
+            assert!(!b.is_finite());
+            assert!(b.is_empty());
+            assert!(!b.add_point(&Point::new(1.0, 2.0, 3.0)).is_empty());
+        }
+
+        #[test]
+        fn adding_points() {
+            let b = BoundingBox::empty()
+                .add_point(&Point::new(-5.0, 2.0, 0.0))
+                .add_point(&Point::new(7.0, 0.0, -3.0));
+
+            assert_eq!(b.min, Point::new(-5.0, 0.0, -3.0));
+            assert_eq!(b.max, Point::new(7.0, 2.0, 0.0));
+        }
+
+        #[test]
+        fn merging_boxes() {
+            let b1 = BoundingBox::new(Point::new(-5.0, -2.0, 0.0), Point::new(7.0, 4.0, 4.0));
+            let b2 = BoundingBox::new(Point::new(8.0, -7.0, -2.0), Point::new(14.0, 2.0, 8.0));
+
+            let b = b1.merge(&b2);
+            assert_eq!(b.min, Point::new(-5.0, -7.0, -2.0));
+            assert_eq!(b.max, Point::new(14.0, 4.0, 8.0));
+        }
+    }
+
+    mod transform {
+        use super::*;
+        use std::f64::consts::SQRT_2;
+
+        #[test]
+        fn rotated_box() {
+            let b = BoundingBox::new(Point::new(-1.0, -1.0, -1.0), Point::new(1.0, 1.0, 1.0));
+            let transform = Transform::default()
+                .rotation_y(std::f64::consts::PI / 4.0)
+                .rotation_x(std::f64::consts::PI / 4.0)
+                .build();
+
+            let b = b.transform(&transform);
+            assert_eq!(b.min, Point::new(-SQRT_2, -1.70711, -1.70711));
+            assert_eq!(b.max, Point::new(SQRT_2, 1.70711, 1.70711));
+        }
+
+        #[test]
+        fn infinite_box_keeps_finite_axes() {
+            let b = BoundingBox::new(
+                Point::new(f64::NEG_INFINITY, 0.0, f64::NEG_INFINITY),
+                Point::new(f64::INFINITY, 0.0, f64::INFINITY),
+            );
+
+            let b = b.transform(&Transform::default().translation(0.0, 2.0, 0.0).build());
+            assert_eq!(b.min.y(), 2.0);
+            assert_eq!(b.max.y(), 2.0);
+            assert_eq!(b.min.x(), f64::NEG_INFINITY);
+            assert_eq!(b.max.z(), f64::INFINITY);
+        }
+    }
+
+    mod intersects {
+        use super::*;
+
+        #[test]
+        fn ray_hits_and_misses_cube() {
+            let b = BoundingBox::new(Point::new(-1.0, -1.0, -1.0), Point::new(1.0, 1.0, 1.0));
+            let cases = [
+                (Point::new(5.0, 0.5, 0.0), Vector::new(-1.0, 0.0, 0.0), true),
+                (Point::new(0.0, 0.5, 0.0), Vector::new(0.0, 0.0, 1.0), true),
+                (
+                    Point::new(-2.0, 0.0, 0.0),
+                    Vector::new(2.0, 4.0, 6.0),
+                    false,
+                ),
+                (
+                    Point::new(2.0, 0.0, 2.0),
+                    Vector::new(0.0, 0.0, -1.0),
+                    false,
+                ),
+                (Point::new(0.0, 0.0, 5.0), Vector::new(0.0, 0.0, 1.0), false),
+            ];
+
+            for (origin, direction, expected) in cases {
+                let ray = Ray::new(origin, direction.normalize());
+                assert_eq!(b.intersects(&ray), expected);
+            }
+        }
+
+        #[test]
+        fn empty_box_is_never_hit() {
+            let b = BoundingBox::empty();
+
+            assert!(!b.intersects(&Ray::new(
+                Point::new(0.0, 0.0, 0.0),
+                Vector::new(0.0, 0.0, 1.0),
+            )));
+            assert!(!b.intersects(&Ray::new(
+                Point::new(5.0, -2.0, 0.0),
+                Vector::new(1.0, 1.0, 1.0).normalize(),
+            )));
+        }
+
+        #[test]
+        fn flat_box() {
+            let b = BoundingBox::new(Point::new(-1.0, 0.0, -1.0), Point::new(1.0, 0.0, 1.0));
+
+            assert!(b.intersects(&Ray::new(
+                Point::new(0.0, 1.0, 0.0),
+                Vector::new(0.0, -1.0, 0.0),
+            )));
+            assert!(!b.intersects(&Ray::new(
+                Point::new(0.0, 1.0, 0.0),
+                Vector::new(1.0, 0.0, 0.0),
+            )));
+        }
+    }
+}
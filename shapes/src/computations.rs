@@ -9,6 +9,10 @@ pub struct Computations<'a> {
     pub normal_v: Vector,
     pub inside: bool,
     pub over_point: Point,
+    pub under_point: Point,
+    pub reflect_v: Vector,
+    pub n1: f64,
+    pub n2: f64,
 }
 
 impl<'a> Computations<'a> {
@@ -19,6 +23,7 @@ impl<'a> Computations<'a> {
         eye_v: Vector,
         normal_v: Vector,
         inside: bool,
+        reflect_v: Vector,
     ) -> Self {
         Self {
             t,
@@ -28,6 +33,33 @@ impl<'a> Computations<'a> {
             normal_v,
             inside,
             over_point: &point + &(&normal_v * 0.0001),
+            under_point: &point - &(&normal_v * 0.0001),
+            reflect_v,
+            n1: 1.0,
+            n2: 1.0,
         }
     }
+
+    pub fn with_refractive_indices(mut self, n1: f64, n2: f64) -> Self {
+        self.n1 = n1;
+        self.n2 = n2;
+        self
+    }
+
+    pub fn schlick(&self) -> f64 {
+        let mut cos = self.eye_v.dot(&self.normal_v);
+
+        if self.n1 > self.n2 {
+            let n = self.n1 / self.n2;
+            let sin2_t = n.powi(2) * (1.0 - cos.powi(2));
+            if sin2_t > 1.0 {
+                return 1.0;
+            }
+
+            cos = (1.0 - sin2_t).sqrt();
+        }
+
+        let r0 = ((self.n1 - self.n2) / (self.n1 + self.n2)).powi(2);
+        r0 + (1.0 - r0) * (1.0 - cos).powi(5)
+    }
 }
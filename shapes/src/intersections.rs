@@ -5,24 +5,75 @@ use math::Ray;
 pub struct Intersection<'a> {
     pub t: f64,
     pub shape: &'a Shape,
+    pub u: f64,
+    pub v: f64,
 }
 
 impl<'a> Intersection<'a> {
     pub fn new(t: f64, shape: &'a Shape) -> Self {
-        Self { t, shape }
+        Self::with_uv(t, shape, 0.0, 0.0)
     }
 
-    pub fn prepare_computations(&self, ray: Ray) -> Computations {
-        let Intersection { t, shape } = self;
+    pub fn with_uv(t: f64, shape: &'a Shape, u: f64, v: f64) -> Self {
+        Self { t, shape, u, v }
+    }
+
+    pub fn prepare_computations(
+        &self,
+        ray: Ray,
+        intersections: &[Intersection<'a>],
+    ) -> Computations<'a> {
+        let Intersection { t, shape, .. } = self;
 
         let point = ray.position(*t);
         let eye_v = -&ray.direction;
-        let mut normal_v = shape.normal_at(&point);
+        let mut normal_v = shape.normal_at_hit(&point, self);
 
         let inside = normal_v.dot(&eye_v) < 0.0;
         normal_v = if inside { -&normal_v } else { normal_v };
+        let reflect_v = ray.direction.reflect(&normal_v);
+
+        let (n1, n2) = self.refractive_indices(intersections);
+
+        Computations::new(*t, shape, point, eye_v, normal_v, inside, reflect_v)
+            .with_refractive_indices(n1, n2)
+    }
+
+    fn refractive_indices(&self, intersections: &[Intersection<'a>]) -> (f64, f64) {
+        let mut containers: Vec<&Shape> = vec![];
+        let mut n1 = 1.0;
+
+        for intersection in intersections {
+            let is_hit = self.is_same(intersection);
+            if is_hit {
+                n1 = containers
+                    .last()
+                    .map_or(1.0, |shape| shape.get_material().refractive_index);
+            }
+
+            match containers
+                .iter()
+                .position(|shape| std::ptr::eq(*shape, intersection.shape))
+            {
+                Some(index) => {
+                    containers.remove(index);
+                }
+                None => containers.push(intersection.shape),
+            }
+
+            if is_hit {
+                let n2 = containers
+                    .last()
+                    .map_or(1.0, |shape| shape.get_material().refractive_index);
+                return (n1, n2);
+            }
+        }
+
+        (n1, 1.0)
+    }
 
-        Computations::new(*t, shape, point, eye_v, normal_v, inside)
+    fn is_same(&self, other: &Intersection) -> bool {
+        self.t == other.t && std::ptr::eq(self.shape, other.shape)
     }
 }
 
@@ -30,14 +81,23 @@ pub fn find_hit<'a>(intersections: &'a [Intersection<'a>]) -> Option<&'a Interse
     intersections
         .iter()
         .filter(|i| i.t >= 0.0)
-        .min_by(|a, b| a.t.partial_cmp(&b.t).unwrap())
+        .min_by(|a, b| a.t.total_cmp(&b.t))
 }
 
 #[cfg(test)]
 mod test {
-    use crate::{Intersection, Material, Sphere};
+    use crate::{Intersection, Material, Shape, Sphere};
     use core::{Point, Vector};
-    use math::{Matrix4, Ray};
+    use math::{Matrix4, Ray, Transform};
+
+    fn glass_sphere(transform: Matrix4, refractive_index: f64) -> Shape {
+        let material = Material {
+            transparency: 1.0,
+            refractive_index,
+            ..Default::default()
+        };
+        Sphere::new(transform, material)
+    }
 
     mod creation {
         use super::*;
@@ -134,19 +194,34 @@ mod test {
             let hit = find_hit(&intersections);
             assert_eq!(hit.unwrap().t, i4.t);
         }
+
+        #[test]
+        fn nan_is_never_the_hit() {
+            let sphere = Sphere::new(Matrix4::identity(), Material::default());
+
+            let intersections = vec![
+                Intersection::new(f64::NAN, &sphere),
+                Intersection::new(3.0, &sphere),
+                Intersection::new(f64::INFINITY, &sphere),
+            ];
+
+            assert_eq!(find_hit(&intersections).unwrap().t, 3.0);
+        }
     }
 
     mod prepare_computations {
         use super::*;
+        use crate::Plane;
+        use std::f64::consts::FRAC_1_SQRT_2;
 
         #[test]
         fn prepare_computations() {
             let shape = Sphere::new(Matrix4::identity(), Material::default());
             let intersection = Intersection::new(4.0, &shape);
-            let computations = intersection.prepare_computations(Ray::new(
-                Point::new(0.0, 0.0, -5.0),
-                Vector::new(0.0, 0.0, 1.0),
-            ));
+            let computations = intersection.prepare_computations(
+                Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0)),
+                &[intersection],
+            );
 
             assert_eq!(computations.t, 4.0);
             assert_eq!(computations.point, Point::new(0.0, 0.0, -1.0));
@@ -158,10 +233,10 @@ mod test {
         fn prepare_computations_inside() {
             let shape = Sphere::new(Matrix4::identity(), Material::default());
             let intersection = Intersection::new(1.0, &shape);
-            let computations = intersection.prepare_computations(Ray::new(
-                Point::new(0.0, 0.0, 0.0),
-                Vector::new(0.0, 0.0, 1.0),
-            ));
+            let computations = intersection.prepare_computations(
+                Ray::new(Point::new(0.0, 0.0, 0.0), Vector::new(0.0, 0.0, 1.0)),
+                &[intersection],
+            );
 
             assert_eq!(computations.t, 1.0);
             assert_eq!(computations.point, Point::new(0.0, 0.0, 1.0));
@@ -169,5 +244,146 @@ mod test {
             assert_eq!(computations.normal_v, Vector::new(0.0, 0.0, -1.0));
             assert!(computations.inside);
         }
+
+        #[test]
+        fn prepare_computations_reflection_vector() {
+            let shape = Plane::new(Matrix4::identity(), Material::default());
+            let intersection = Intersection::new(2.0_f64.sqrt(), &shape);
+            let computations = intersection.prepare_computations(
+                Ray::new(
+                    Point::new(0.0, 1.0, -1.0),
+                    Vector::new(0.0, -FRAC_1_SQRT_2, FRAC_1_SQRT_2),
+                ),
+                &[intersection],
+            );
+
+            assert_eq!(
+                computations.reflect_v,
+                Vector::new(0.0, FRAC_1_SQRT_2, FRAC_1_SQRT_2)
+            );
+        }
+
+        #[test]
+        fn prepare_computations_under_point() {
+            let shape = glass_sphere(Transform::default().translation(0.0, 0.0, 1.0).build(), 1.5);
+            let intersection = Intersection::new(5.0, &shape);
+            let computations = intersection.prepare_computations(
+                Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0)),
+                &[intersection],
+            );
+
+            assert!(computations.under_point.z() > 0.0001 / 2.0);
+            assert!(computations.point.z() < computations.under_point.z());
+        }
+
+        #[test]
+        fn n1_and_n2_at_various_intersections() {
+            let a = glass_sphere(Transform::default().scaling(2.0, 2.0, 2.0).build(), 1.5);
+            let b = glass_sphere(
+                Transform::default().translation(0.0, 0.0, -0.25).build(),
+                2.0,
+            );
+            let c = glass_sphere(
+                Transform::default().translation(0.0, 0.0, 0.25).build(),
+                2.5,
+            );
+
+            let ray = Ray::new(Point::new(0.0, 0.0, -4.0), Vector::new(0.0, 0.0, 1.0));
+            let xs = vec![
+                Intersection::new(2.0, &a),
+                Intersection::new(2.75, &b),
+                Intersection::new(3.25, &c),
+                Intersection::new(4.75, &b),
+                Intersection::new(5.25, &c),
+                Intersection::new(6.0, &a),
+            ];
+            let expected = [
+                (1.0, 1.5),
+                (1.5, 2.0),
+                (2.0, 2.5),
+                (2.5, 2.5),
+                (2.5, 1.5),
+                (1.5, 1.0),
+            ];
+
+            for (intersection, (n1, n2)) in xs.iter().zip(expected) {
+                let computations = intersection.prepare_computations(ray, &xs);
+                assert_eq!(computations.n1, n1);
+                assert_eq!(computations.n2, n2);
+            }
+        }
+    }
+
+    mod smooth_triangle {
+        use super::*;
+        use crate::SmoothTriangle;
+
+        #[test]
+        fn prepare_computations_interpolates_normal() {
+            let triangle = SmoothTriangle::new(
+                Matrix4::identity(),
+                Material::default(),
+                [
+                    Point::new(0.0, 1.0, 0.0),
+                    Point::new(-1.0, 0.0, 0.0),
+                    Point::new(1.0, 0.0, 0.0),
+                ],
+                [
+                    Vector::new(0.0, 1.0, 0.0),
+                    Vector::new(-1.0, 0.0, 0.0),
+                    Vector::new(1.0, 0.0, 0.0),
+                ],
+            );
+            let intersection = Intersection::with_uv(1.0, &triangle, 0.45, 0.25);
+            let computations = intersection.prepare_computations(
+                Ray::new(Point::new(-0.2, 0.3, -2.0), Vector::new(0.0, 0.0, 1.0)),
+                &[intersection],
+            );
+
+            assert_eq!(computations.normal_v, Vector::new(-0.5547, 0.83205, 0.0));
+        }
+    }
+
+    mod schlick {
+        use super::*;
+        use std::f64::consts::FRAC_1_SQRT_2;
+
+        #[test]
+        fn total_internal_reflection() {
+            let shape = glass_sphere(Matrix4::identity(), 1.5);
+            let ray = Ray::new(
+                Point::new(0.0, 0.0, FRAC_1_SQRT_2),
+                Vector::new(0.0, 1.0, 0.0),
+            );
+            let xs = vec![
+                Intersection::new(-FRAC_1_SQRT_2, &shape),
+                Intersection::new(FRAC_1_SQRT_2, &shape),
+            ];
+
+            assert_eq!(xs[1].prepare_computations(ray, &xs).schlick(), 1.0);
+        }
+
+        #[test]
+        fn perpendicular_viewing_angle() {
+            let shape = glass_sphere(Matrix4::identity(), 1.5);
+            let ray = Ray::new(Point::new(0.0, 0.0, 0.0), Vector::new(0.0, 1.0, 0.0));
+            let xs = vec![
+                Intersection::new(-1.0, &shape),
+                Intersection::new(1.0, &shape),
+            ];
+
+            let reflectance = xs[1].prepare_computations(ray, &xs).schlick();
+            assert!((reflectance - 0.04).abs() < 0.00001);
+        }
+
+        #[test]
+        fn small_angle_and_n2_greater_than_n1() {
+            let shape = glass_sphere(Matrix4::identity(), 1.5);
+            let ray = Ray::new(Point::new(0.0, 0.99, -2.0), Vector::new(0.0, 0.0, 1.0));
+            let xs = vec![Intersection::new(1.8589, &shape)];
+
+            let reflectance = xs[0].prepare_computations(ray, &xs).schlick();
+            assert!((reflectance - 0.48873).abs() < 0.00001);
+        }
     }
 }
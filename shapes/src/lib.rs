@@ -1,15 +1,29 @@
+mod bounds;
 mod computations;
 mod intersections;
 mod materials;
+mod obj_parser;
 mod pattern;
 mod shape;
 
+pub use self::bounds::BoundingBox;
 pub use self::computations::Computations;
 pub use self::intersections::find_hit;
 pub use self::intersections::Intersection;
 pub use self::materials::Material;
+pub use self::obj_parser::parse_obj;
+pub use self::obj_parser::ObjModel;
+pub use self::obj_parser::ObjParseError;
 pub use self::pattern::Patn;
 pub use self::pattern::Pattern;
+pub use self::shape::Cone;
+pub use self::shape::Csg;
+pub use self::shape::CsgOperation;
+pub use self::shape::Cube;
+pub use self::shape::Cylinder;
+pub use self::shape::Group;
 pub use self::shape::Plane;
 pub use self::shape::Shape;
+pub use self::shape::SmoothTriangle;
 pub use self::shape::Sphere;
+pub use self::shape::Triangle;
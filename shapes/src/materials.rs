@@ -9,6 +9,9 @@ pub struct Material {
     pub diffuse: f64,
     pub specular: f64,
     pub shininess: f64,
+    pub reflective: f64,
+    pub transparency: f64,
+    pub refractive_index: f64,
 }
 
 impl Material {
@@ -28,6 +31,9 @@ impl Default for Material {
             diffuse: 0.9,
             specular: 0.9,
             shininess: 200.0,
+            reflective: 0.0,
+            transparency: 0.0,
+            refractive_index: 1.0,
         }
     }
 }
@@ -71,6 +77,9 @@ mod test {
             assert_eq!(m.diffuse, 0.9);
             assert_eq!(m.specular, 0.9);
             assert_eq!(m.shininess, 200.0);
+            assert_eq!(m.reflective, 0.0);
+            assert_eq!(m.transparency, 0.0);
+            assert_eq!(m.refractive_index, 1.0);
         }
     }
 }
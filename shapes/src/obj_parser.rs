@@ -0,0 +1,512 @@
+use crate::{Group, Material, Shape, SmoothTriangle, Triangle};
+use core::{Point, Vector};
+use math::Matrix4;
+use std::fmt;
+
+// Triangles whose corner angle has a sine below this are lines, whatever their size.
+const DEGENERATE_SINE: f64 = 1e-10;
+
+#[derive(Debug, PartialEq)]
+pub struct ObjParseError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ObjParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for ObjParseError {}
+
+#[derive(Debug)]
+pub struct ObjModel {
+    pub vertices: Vec<Point>,
+    pub normals: Vec<Vector>,
+    pub default_group: Vec<Shape>,
+    pub groups: Vec<(String, Vec<Shape>)>,
+    pub ignored_lines: usize,
+    /// Triangles dropped because their corners are collinear and so have no normal.
+    pub degenerate_triangles: usize,
+}
+
+impl ObjModel {
+    pub fn group(&self, name: &str) -> Option<&Vec<Shape>> {
+        self.groups
+            .iter()
+            .find(|(group_name, _)| group_name == name)
+            .map(|(_, shapes)| shapes)
+    }
+
+    pub fn into_shapes(self) -> Vec<Shape> {
+        let mut shapes = self.default_group;
+        for (_, group) in self.groups {
+            shapes.extend(group);
+        }
+        shapes
+    }
+
+    pub fn into_group(self, transform: Matrix4) -> Shape {
+        let mut children = self.default_group;
+        for (_, group) in self.groups {
+            children.push(Group::new(Matrix4::identity(), group));
+        }
+        Group::new(transform, children)
+    }
+}
+
+struct FaceVertex {
+    vertex: usize,
+    normal: Option<usize>,
+}
+
+pub fn parse_obj(input: &str, material: Material) -> Result<ObjModel, ObjParseError> {
+    let mut model = ObjModel {
+        vertices: vec![],
+        normals: vec![],
+        default_group: vec![],
+        groups: vec![],
+        ignored_lines: 0,
+        degenerate_triangles: 0,
+    };
+    let mut current_group: Option<usize> = None;
+
+    for (index, line) in input.lines().enumerate() {
+        let line_number = index + 1;
+        let mut tokens = line.split_whitespace();
+        let error = |message: String| ObjParseError {
+            line: line_number,
+            message,
+        };
+
+        match tokens.next() {
+            None => {}
+            Some(token) if token.starts_with('#') => {}
+            Some("v") => {
+                let [x, y, z] = parse_coordinates(tokens).map_err(error)?;
+                model.vertices.push(Point::new(x, y, z));
+            }
+            Some("vn") => {
+                let [x, y, z] = parse_coordinates(tokens).map_err(error)?;
+                model.normals.push(Vector::new(x, y, z));
+            }
+            Some("f") => {
+                let face = tokens
+                    .map(|token| parse_face_vertex(token, &model))
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(error)?;
+                if face.len() < 3 {
+                    return Err(error(format!(
+                        "face needs at least 3 vertices, found {}",
+                        face.len()
+                    )));
+                }
+
+                let triangles = fan_triangulation(&face, &model, material);
+                model.degenerate_triangles += face.len() - 2 - triangles.len();
+                match current_group {
+                    Some(group) => model.groups[group].1.extend(triangles),
+                    None => model.default_group.extend(triangles),
+                }
+            }
+            Some("g") => {
+                let name = tokens.collect::<Vec<_>>().join(" ");
+                if name.is_empty() {
+                    return Err(error("group statement is missing a name".to_string()));
+                }
+
+                current_group = match model.groups.iter().position(|(n, _)| *n == name) {
+                    Some(group) => Some(group),
+                    None => {
+                        model.groups.push((name, vec![]));
+                        Some(model.groups.len() - 1)
+                    }
+                };
+            }
+            Some(_) => model.ignored_lines += 1,
+        }
+    }
+
+    Ok(model)
+}
+
+fn parse_coordinates<'a>(tokens: impl Iterator<Item = &'a str>) -> Result<[f64; 3], String> {
+    let values = tokens
+        .map(|token| {
+            token
+                .parse::<f64>()
+                .map_err(|_| format!("invalid number '{}'", token))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // An optional w weight, or the r g b vertex colours some exporters append, are
+    // ignored.
+    match values[..] {
+        [x, y, z] | [x, y, z, _] | [x, y, z, _, _, _] => Ok([x, y, z]),
+        _ => Err(format!("expected 3 coordinates, found {}", values.len())),
+    }
+}
+
+fn parse_face_vertex(token: &str, model: &ObjModel) -> Result<FaceVertex, String> {
+    let mut parts = token.split('/');
+    let vertex = resolve_index(parts.next().unwrap_or(""), model.vertices.len(), "vertex")?;
+    let _texture = parts.next();
+    let normal = match parts.next() {
+        Some(part) if !part.is_empty() => Some(resolve_index(part, model.normals.len(), "normal")?),
+        _ => None,
+    };
+
+    Ok(FaceVertex { vertex, normal })
+}
+
+fn resolve_index(token: &str, count: usize, kind: &str) -> Result<usize, String> {
+    let index = token
+        .parse::<i64>()
+        .map_err(|_| format!("invalid {} index '{}'", kind, token))?;
+
+    let resolved = if index > 0 {
+        index - 1
+    } else {
+        count as i64 + index
+    };
+
+    if index == 0 || resolved < 0 || resolved >= count as i64 {
+        return Err(format!(
+            "{} index {} is out of range ({} defined)",
+            kind, index, count
+        ));
+    }
+
+    Ok(resolved as usize)
+}
+
+fn fan_triangulation(face: &[FaceVertex], model: &ObjModel, material: Material) -> Vec<Shape> {
+    (1..face.len() - 1)
+        .filter_map(|i| {
+            let corners = [&face[0], &face[i], &face[i + 1]];
+            let points = corners.map(|corner| model.vertices[corner.vertex]);
+            if is_degenerate(&points) {
+                return None;
+            }
+
+            Some(match corners.map(|corner| corner.normal) {
+                [Some(n1), Some(n2), Some(n3)] => SmoothTriangle::new(
+                    Matrix4::identity(),
+                    material,
+                    points,
+                    [model.normals[n1], model.normals[n2], model.normals[n3]],
+                ),
+                _ => Triangle::new(Matrix4::identity(), material, points),
+            })
+        })
+        .collect()
+}
+
+// |e1 x e2| = |e1| |e2| sin(angle), so comparing against the edge lengths keeps
+// the test independent of the mesh's scale. Zero-length edges are degenerate too.
+fn is_degenerate([p1, p2, p3]: &[Point; 3]) -> bool {
+    let (e1, e2) = (p2 - p1, p3 - p1);
+    e1.cross(&e2).magnitude() <= DEGENERATE_SINE * e1.magnitude() * e2.magnitude()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::obj_parser::parse_obj;
+    use crate::{Material, Shape};
+    use core::{Point, Vector};
+
+    fn points(shape: &Shape) -> [Point; 3] {
+        match shape {
+            Shape::Triangle(t) => [t.p1, t.p2, t.p3],
+            Shape::SmoothTriangle(t) => [t.p1, t.p2, t.p3],
+            _ => panic!("expected a triangle"),
+        }
+    }
+
+    mod statements {
+        use super::*;
+
+        #[test]
+        fn ignores_unrecognised_lines() {
+            let input = "There was a young lady named Bright\n\
+                         who traveled much faster than light.\n\
+                         She set out one day\n\
+                         in a relative way,\n\
+                         and came back the previous night.\n";
+
+            let model = parse_obj(input, Material::default()).unwrap();
+            assert_eq!(model.ignored_lines, 5);
+        }
+
+        #[test]
+        fn skips_comments_and_blank_lines() {
+            let input = "# exported from blender\n\nv 1 2 3\n";
+
+            let model = parse_obj(input, Material::default()).unwrap();
+            assert_eq!(model.ignored_lines, 0);
+            assert_eq!(model.vertices.len(), 1);
+        }
+
+        #[test]
+        fn vertex_records() {
+            let input = "v -1 1 0\n\
+                         v -1.0000 0.5000 0.0000\n\
+                         v 1 0 0\n\
+                         v 1 1 0\n";
+
+            let model = parse_obj(input, Material::default()).unwrap();
+            assert_eq!(model.vertices[0], Point::new(-1.0, 1.0, 0.0));
+            assert_eq!(model.vertices[1], Point::new(-1.0, 0.5, 0.0));
+            assert_eq!(model.vertices[2], Point::new(1.0, 0.0, 0.0));
+            assert_eq!(model.vertices[3], Point::new(1.0, 1.0, 0.0));
+        }
+
+        #[test]
+        fn vertex_colours_are_ignored() {
+            let input = "v 1 2 3 0.5 0.25 1.0\n";
+
+            let model = parse_obj(input, Material::default()).unwrap();
+            assert_eq!(model.vertices, vec![Point::new(1.0, 2.0, 3.0)]);
+        }
+
+        #[test]
+        fn vertex_normal_records() {
+            let input = "vn 0 0 1\n\
+                         vn 0.707 0 -0.707\n\
+                         vn 1 2 3\n";
+
+            let model = parse_obj(input, Material::default()).unwrap();
+            assert_eq!(model.normals[0], Vector::new(0.0, 0.0, 1.0));
+            assert_eq!(model.normals[1], Vector::new(0.707, 0.0, -0.707));
+            assert_eq!(model.normals[2], Vector::new(1.0, 2.0, 3.0));
+        }
+    }
+
+    mod faces {
+        use super::*;
+
+        #[test]
+        fn triangle_faces() {
+            let input = "v -1 1 0\n\
+                         v -1 0 0\n\
+                         v 1 0 0\n\
+                         v 1 1 0\n\
+                         \n\
+                         f 1 2 3\n\
+                         f 1 3 4\n";
+
+            let model = parse_obj(input, Material::default()).unwrap();
+            let v = &model.vertices;
+            assert_eq!(model.default_group.len(), 2);
+            assert_eq!(points(&model.default_group[0]), [v[0], v[1], v[2]]);
+            assert_eq!(points(&model.default_group[1]), [v[0], v[2], v[3]]);
+        }
+
+        #[test]
+        fn polygon_fan_triangulation() {
+            let input = "v -1 1 0\n\
+                         v -1 0 0\n\
+                         v 1 0 0\n\
+                         v 1 1 0\n\
+                         v 0 2 0\n\
+                         \n\
+                         f 1 2 3 4 5\n";
+
+            let model = parse_obj(input, Material::default()).unwrap();
+            let v = &model.vertices;
+            assert_eq!(model.default_group.len(), 3);
+            assert_eq!(points(&model.default_group[0]), [v[0], v[1], v[2]]);
+            assert_eq!(points(&model.default_group[1]), [v[0], v[2], v[3]]);
+            assert_eq!(points(&model.default_group[2]), [v[0], v[3], v[4]]);
+        }
+
+        #[test]
+        fn faces_with_normals() {
+            let input = "v 0 1 0\n\
+                         v -1 0 0\n\
+                         v 1 0 0\n\
+                         \n\
+                         vn -1 0 0\n\
+                         vn 1 0 0\n\
+                         vn 0 1 0\n\
+                         \n\
+                         f 1//3 2//1 3//2\n\
+                         f 1/0/3 2/102/1 3/14/2\n";
+
+            let model = parse_obj(input, Material::default()).unwrap();
+            assert_eq!(model.default_group.len(), 2);
+            for shape in &model.default_group {
+                match shape {
+                    Shape::SmoothTriangle(t) => {
+                        assert_eq!(t.p1, model.vertices[0]);
+                        assert_eq!(t.n1, model.normals[2]);
+                        assert_eq!(t.n2, model.normals[0]);
+                        assert_eq!(t.n3, model.normals[1]);
+                    }
+                    _ => panic!("expected a smooth triangle"),
+                }
+            }
+        }
+
+        #[test]
+        fn negative_indices_are_relative() {
+            let input = "v -1 1 0\n\
+                         v -1 0 0\n\
+                         v 1 0 0\n\
+                         f -3 -2 -1\n";
+
+            let model = parse_obj(input, Material::default()).unwrap();
+            let v = &model.vertices;
+            assert_eq!(points(&model.default_group[0]), [v[0], v[1], v[2]]);
+        }
+    }
+
+    mod groups {
+        use super::*;
+        use math::Matrix4;
+
+        #[test]
+        fn triangles_in_named_groups() {
+            let input = "v -1 1 0\n\
+                         v -1 0 0\n\
+                         v 1 0 0\n\
+                         v 1 1 0\n\
+                         \n\
+                         g FirstGroup\n\
+                         f 1 2 3\n\
+                         g SecondGroup\n\
+                         f 1 3 4\n";
+
+            let model = parse_obj(input, Material::default()).unwrap();
+            let v = &model.vertices;
+            assert!(model.default_group.is_empty());
+            assert_eq!(
+                points(&model.group("FirstGroup").unwrap()[0]),
+                [v[0], v[1], v[2]]
+            );
+            assert_eq!(
+                points(&model.group("SecondGroup").unwrap()[0]),
+                [v[0], v[2], v[3]]
+            );
+            assert_eq!(model.into_shapes().len(), 2);
+        }
+
+        #[test]
+        fn converting_to_a_group() {
+            let input = "v -1 1 0\n\
+                         v -1 0 0\n\
+                         v 1 0 0\n\
+                         v 1 1 0\n\
+                         \n\
+                         f 1 2 4\n\
+                         g FirstGroup\n\
+                         f 1 2 3\n\
+                         g SecondGroup\n\
+                         f 1 3 4\n";
+
+            let model = parse_obj(input, Material::default()).unwrap();
+            match model.into_group(Matrix4::identity()) {
+                Shape::Group(group) => {
+                    assert_eq!(group.children.len(), 3);
+                    assert!(matches!(group.children[0], Shape::Triangle(_)));
+                    assert!(matches!(group.children[1], Shape::Group(_)));
+                    assert!(matches!(group.children[2], Shape::Group(_)));
+                }
+                _ => panic!("expected a group"),
+            }
+        }
+
+        #[test]
+        fn repeated_group_names_are_merged() {
+            let input = "v -1 1 0\n\
+                         v -1 0 0\n\
+                         v 1 0 0\n\
+                         g Body\n\
+                         f 1 2 3\n\
+                         g Wheel\n\
+                         f 1 2 3\n\
+                         g Body\n\
+                         f 1 2 3\n";
+
+            let model = parse_obj(input, Material::default()).unwrap();
+            assert_eq!(model.groups.len(), 2);
+            assert_eq!(model.group("Body").unwrap().len(), 2);
+        }
+    }
+
+    mod errors {
+        use super::*;
+
+        #[test]
+        fn malformed_vertex_reports_line() {
+            let input = "v 1 2 3\nv 1 two 3\n";
+
+            let error = parse_obj(input, Material::default()).unwrap_err();
+            assert_eq!(error.line, 2);
+            assert_eq!(error.to_string(), "line 2: invalid number 'two'");
+        }
+
+        #[test]
+        fn missing_coordinate() {
+            let error = parse_obj("v 1 2\n", Material::default()).unwrap_err();
+            assert_eq!(error.line, 1);
+        }
+
+        #[test]
+        fn vertex_index_out_of_range() {
+            let input = "v -1 1 0\n\
+                         v -1 0 0\n\
+                         \n\
+                         f 1 2 3\n";
+
+            let error = parse_obj(input, Material::default()).unwrap_err();
+            assert_eq!(error.line, 4);
+            assert_eq!(error.message, "vertex index 3 is out of range (2 defined)");
+        }
+
+        #[test]
+        fn degenerate_faces_are_dropped() {
+            let input = "v 0 0 0\n\
+                         v 1 0 0\n\
+                         v 2 0 0\n\
+                         v 0 1 0\n\
+                         f 1 2 3\n\
+                         f 1 2 3 4\n";
+
+            let model = parse_obj(input, Material::default()).unwrap();
+            assert_eq!(model.degenerate_triangles, 2);
+            assert_eq!(model.default_group.len(), 1);
+            assert_eq!(
+                points(&model.default_group[0]),
+                [
+                    Point::new(0.0, 0.0, 0.0),
+                    Point::new(2.0, 0.0, 0.0),
+                    Point::new(0.0, 1.0, 0.0)
+                ]
+            );
+        }
+
+        #[test]
+        fn tiny_triangles_are_kept() {
+            let input = "v 0 0 0\n\
+                         v 0.000001 0 0\n\
+                         v 0 0.000001 0\n\
+                         f 1 2 3\n";
+
+            let model = parse_obj(input, Material::default()).unwrap();
+            assert_eq!(model.degenerate_triangles, 0);
+            assert_eq!(model.default_group.len(), 1);
+        }
+
+        #[test]
+        fn face_with_too_few_vertices() {
+            let input = "v -1 1 0\n\
+                         v -1 0 0\n\
+                         f 1 2\n";
+
+            let error = parse_obj(input, Material::default()).unwrap_err();
+            assert_eq!(error.line, 3);
+        }
+    }
+}
@@ -1,7 +1,9 @@
-use crate::{Intersection, Material};
+use crate::{BoundingBox, Intersection, Material};
 use core::{Colour, Point, Vector};
 use math::{Matrix4, Ray};
 
+const EPSILON: f64 = 0.0001;
+
 #[derive(Debug)]
 
 pub struct ShapeProperties {
@@ -22,10 +24,91 @@ pub struct Plane {
     pub properties: ShapeProperties,
 }
 
+#[derive(Debug)]
+
+pub struct Cube {
+    pub properties: ShapeProperties,
+}
+
+#[derive(Debug)]
+
+pub struct Cylinder {
+    pub properties: ShapeProperties,
+    pub minimum: f64,
+    pub maximum: f64,
+    pub closed: bool,
+}
+
+#[derive(Debug)]
+
+pub struct Cone {
+    pub properties: ShapeProperties,
+    pub minimum: f64,
+    pub maximum: f64,
+    pub closed: bool,
+}
+
+#[derive(Debug)]
+
+pub struct Triangle {
+    pub properties: ShapeProperties,
+    pub p1: Point,
+    pub p2: Point,
+    pub p3: Point,
+    pub e1: Vector,
+    pub e2: Vector,
+    pub normal: Vector,
+}
+
+#[derive(Debug)]
+
+pub struct SmoothTriangle {
+    pub properties: ShapeProperties,
+    pub p1: Point,
+    pub p2: Point,
+    pub p3: Point,
+    pub n1: Vector,
+    pub n2: Vector,
+    pub n3: Vector,
+    pub e1: Vector,
+    pub e2: Vector,
+}
+
+#[derive(Debug)]
+
+pub struct Group {
+    pub properties: ShapeProperties,
+    pub children: Vec<Shape>,
+    pub bounds: BoundingBox,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum CsgOperation {
+    Union,
+    Intersection,
+    Difference,
+}
+
+#[derive(Debug)]
+
+pub struct Csg {
+    pub properties: ShapeProperties,
+    pub operation: CsgOperation,
+    pub left: Box<Shape>,
+    pub right: Box<Shape>,
+}
+
 #[derive(Debug)]
 pub enum Shape {
     Sphere(Sphere),
     Plane(Plane),
+    Cube(Cube),
+    Cylinder(Cylinder),
+    Cone(Cone),
+    Triangle(Triangle),
+    SmoothTriangle(SmoothTriangle),
+    Group(Group),
+    Csg(Csg),
 }
 
 #[allow(clippy::new_ret_no_self)]
@@ -56,10 +139,241 @@ impl Plane {
     }
 }
 
+#[allow(clippy::new_ret_no_self)]
+impl Cube {
+    pub fn new(transform: Matrix4, material: Material) -> Shape {
+        let inverse_transform = transform.inverse();
+        Shape::Cube(Self {
+            properties: ShapeProperties {
+                transform,
+                inverse_transform,
+                material,
+            },
+        })
+    }
+}
+
+#[allow(clippy::new_ret_no_self)]
+impl Cylinder {
+    pub fn new(transform: Matrix4, material: Material) -> Shape {
+        Cylinder::truncated(transform, material, f64::NEG_INFINITY, f64::INFINITY, false)
+    }
+
+    pub fn truncated(
+        transform: Matrix4,
+        material: Material,
+        minimum: f64,
+        maximum: f64,
+        closed: bool,
+    ) -> Shape {
+        let inverse_transform = transform.inverse();
+        Shape::Cylinder(Self {
+            properties: ShapeProperties {
+                transform,
+                inverse_transform,
+                material,
+            },
+            minimum,
+            maximum,
+            closed,
+        })
+    }
+}
+
+#[allow(clippy::new_ret_no_self)]
+impl Cone {
+    pub fn new(transform: Matrix4, material: Material) -> Shape {
+        Cone::truncated(transform, material, f64::NEG_INFINITY, f64::INFINITY, false)
+    }
+
+    pub fn truncated(
+        transform: Matrix4,
+        material: Material,
+        minimum: f64,
+        maximum: f64,
+        closed: bool,
+    ) -> Shape {
+        let inverse_transform = transform.inverse();
+        Shape::Cone(Self {
+            properties: ShapeProperties {
+                transform,
+                inverse_transform,
+                material,
+            },
+            minimum,
+            maximum,
+            closed,
+        })
+    }
+}
+
+#[allow(clippy::new_ret_no_self)]
+impl Triangle {
+    pub fn new(transform: Matrix4, material: Material, points: [Point; 3]) -> Shape {
+        let inverse_transform = transform.inverse();
+        let [p1, p2, p3] = points;
+        let e1 = &p2 - &p1;
+        let e2 = &p3 - &p1;
+        let normal = e2.cross(&e1).normalize();
+        Shape::Triangle(Self {
+            properties: ShapeProperties {
+                transform,
+                inverse_transform,
+                material,
+            },
+            p1,
+            p2,
+            p3,
+            e1,
+            e2,
+            normal,
+        })
+    }
+}
+
+#[allow(clippy::new_ret_no_self)]
+impl SmoothTriangle {
+    pub fn new(
+        transform: Matrix4,
+        material: Material,
+        points: [Point; 3],
+        normals: [Vector; 3],
+    ) -> Shape {
+        let inverse_transform = transform.inverse();
+        let [p1, p2, p3] = points;
+        let [n1, n2, n3] = normals;
+        Shape::SmoothTriangle(Self {
+            properties: ShapeProperties {
+                transform,
+                inverse_transform,
+                material,
+            },
+            p1,
+            p2,
+            p3,
+            n1,
+            n2,
+            n3,
+            e1: &p2 - &p1,
+            e2: &p3 - &p1,
+        })
+    }
+}
+
+#[allow(clippy::new_ret_no_self)]
+impl Group {
+    pub fn new(transform: Matrix4, mut children: Vec<Shape>) -> Shape {
+        for child in children.iter_mut() {
+            child.apply_parent_transform(&transform);
+        }
+
+        let inverse_transform = transform.inverse();
+        let bounds = Group::children_bounds(&children);
+        Shape::Group(Self {
+            properties: ShapeProperties {
+                transform,
+                inverse_transform,
+                material: Material::default(),
+            },
+            children,
+            bounds,
+        })
+    }
+
+    fn children_bounds(children: &[Shape]) -> BoundingBox {
+        children.iter().fold(BoundingBox::empty(), |bounds, child| {
+            bounds.merge(&child.bounds())
+        })
+    }
+}
+
+#[allow(clippy::new_ret_no_self)]
+impl Csg {
+    pub fn new(transform: Matrix4, operation: CsgOperation, left: Shape, right: Shape) -> Shape {
+        let mut left = Box::new(left);
+        let mut right = Box::new(right);
+        left.apply_parent_transform(&transform);
+        right.apply_parent_transform(&transform);
+
+        let inverse_transform = transform.inverse();
+        Shape::Csg(Self {
+            properties: ShapeProperties {
+                transform,
+                inverse_transform,
+                material: Material::default(),
+            },
+            operation,
+            left,
+            right,
+        })
+    }
+
+    fn filter_intersections<'a>(
+        &self,
+        intersections: Vec<Intersection<'a>>,
+    ) -> Vec<Intersection<'a>> {
+        let mut in_left = false;
+        let mut in_right = false;
+
+        intersections
+            .into_iter()
+            .filter(|intersection| {
+                let left_hit = self.left.includes(intersection.shape);
+                let allowed = self.operation.allows(left_hit, in_left, in_right);
+
+                if left_hit {
+                    in_left = !in_left;
+                } else {
+                    in_right = !in_right;
+                }
+
+                allowed
+            })
+            .collect()
+    }
+}
+
+impl CsgOperation {
+    fn allows(&self, left_hit: bool, in_left: bool, in_right: bool) -> bool {
+        match self {
+            CsgOperation::Union => (left_hit && !in_right) || (!left_hit && !in_left),
+            CsgOperation::Intersection => (left_hit && in_right) || (!left_hit && in_left),
+            CsgOperation::Difference => (left_hit && !in_right) || (!left_hit && in_left),
+        }
+    }
+}
+
 impl Shape {
-    pub fn intersect(&self, ray: &Ray) -> Vec<Intersection> {
-        let transformed_ray = ray.transform(self.get_inverse_transform());
+    pub fn intersect(&self, ray: &Ray) -> Vec<Intersection<'_>> {
+        match self {
+            Shape::Group(group) => {
+                if !group.bounds.intersects(ray) {
+                    return vec![];
+                }
+
+                // Children already carry the group transform, so they take the world ray.
+                let mut xs: Vec<_> = group
+                    .children
+                    .iter()
+                    .flat_map(|child| child.intersect(ray))
+                    .collect();
+                xs.sort_by(|a, b| a.t.total_cmp(&b.t));
+                xs
+            }
+            Shape::Csg(csg) => {
+                let mut xs = csg.left.intersect(ray);
+                xs.extend(csg.right.intersect(ray));
+                xs.sort_by(|a, b| a.t.total_cmp(&b.t));
+                csg.filter_intersections(xs)
+            }
+            _ => self.local_intersect(&ray.transform(self.get_inverse_transform())),
+        }
+    }
+
+    // Intersects a primitive shape with a ray already transformed into its object space.
+    fn local_intersect(&self, transformed_ray: &Ray) -> Vec<Intersection<'_>> {
         match self {
+            Shape::Group(_) | Shape::Csg(_) => vec![],
             Shape::Sphere(_) => {
                 let sphere_to_ray = &transformed_ray.origin - &Point::origin();
 
@@ -78,65 +392,422 @@ impl Shape {
                 ]
             }
             Shape::Plane(_) => {
-                if transformed_ray.direction.y().abs() < 0.0001 {
+                if transformed_ray.direction.y().abs() < EPSILON {
                     return vec![];
                 }
 
                 let t = -transformed_ray.origin.y() / transformed_ray.direction.y();
                 vec![Intersection::new(t, self)]
             }
+            Shape::Cube(_) => {
+                let origin = &transformed_ray.origin;
+                let direction = &transformed_ray.direction;
+                let (x_tmin, x_tmax) = check_axis(origin.x(), direction.x());
+                let (y_tmin, y_tmax) = check_axis(origin.y(), direction.y());
+                let (z_tmin, z_tmax) = check_axis(origin.z(), direction.z());
+
+                let tmin = x_tmin.max(y_tmin).max(z_tmin);
+                let tmax = x_tmax.min(y_tmax).min(z_tmax);
+
+                if tmin > tmax {
+                    return vec![];
+                }
+
+                vec![Intersection::new(tmin, self), Intersection::new(tmax, self)]
+            }
+            Shape::Cylinder(cylinder) => {
+                let origin = &transformed_ray.origin;
+                let direction = &transformed_ray.direction;
+                let mut xs = vec![];
+
+                let a = direction.x().powi(2) + direction.z().powi(2);
+                if a.abs() >= EPSILON {
+                    let b = 2.0 * origin.x() * direction.x() + 2.0 * origin.z() * direction.z();
+                    let c = origin.x().powi(2) + origin.z().powi(2) - 1.0;
+                    let discriminant = b * b - 4.0 * a * c;
+
+                    if discriminant < 0.0 {
+                        return vec![];
+                    }
+
+                    let t0 = (-b - discriminant.sqrt()) / (2.0 * a);
+                    let t1 = (-b + discriminant.sqrt()) / (2.0 * a);
+                    xs.extend(intersect_sides(
+                        self,
+                        transformed_ray,
+                        &[t0, t1],
+                        cylinder.minimum,
+                        cylinder.maximum,
+                    ));
+                }
+
+                if cylinder.closed {
+                    xs.extend(intersect_caps(
+                        self,
+                        transformed_ray,
+                        cylinder.minimum,
+                        cylinder.maximum,
+                        |_| 1.0,
+                    ));
+                }
+
+                xs
+            }
+            Shape::Cone(cone) => {
+                let origin = &transformed_ray.origin;
+                let direction = &transformed_ray.direction;
+                let mut xs = vec![];
+
+                let a = direction.x().powi(2) - direction.y().powi(2) + direction.z().powi(2);
+                let b = 2.0 * origin.x() * direction.x() - 2.0 * origin.y() * direction.y()
+                    + 2.0 * origin.z() * direction.z();
+                let c = origin.x().powi(2) - origin.y().powi(2) + origin.z().powi(2);
+
+                if a.abs() < EPSILON {
+                    if b.abs() >= EPSILON {
+                        let t = -c / (2.0 * b);
+                        xs.extend(intersect_sides(
+                            self,
+                            transformed_ray,
+                            &[t],
+                            cone.minimum,
+                            cone.maximum,
+                        ));
+                    }
+                } else {
+                    let discriminant = b * b - 4.0 * a * c;
+                    if discriminant >= 0.0 {
+                        let t0 = (-b - discriminant.sqrt()) / (2.0 * a);
+                        let t1 = (-b + discriminant.sqrt()) / (2.0 * a);
+                        let (t0, t1) = if t0 > t1 { (t1, t0) } else { (t0, t1) };
+                        xs.extend(intersect_sides(
+                            self,
+                            transformed_ray,
+                            &[t0, t1],
+                            cone.minimum,
+                            cone.maximum,
+                        ));
+                    }
+                }
+
+                if cone.closed {
+                    xs.extend(intersect_caps(
+                        self,
+                        transformed_ray,
+                        cone.minimum,
+                        cone.maximum,
+                        f64::abs,
+                    ));
+                }
+
+                xs
+            }
+            Shape::Triangle(triangle) => intersect_triangle(
+                self,
+                transformed_ray,
+                &triangle.p1,
+                &triangle.e1,
+                &triangle.e2,
+            ),
+            Shape::SmoothTriangle(triangle) => intersect_triangle(
+                self,
+                transformed_ray,
+                &triangle.p1,
+                &triangle.e1,
+                &triangle.e2,
+            ),
         }
     }
 
+    /// The surface normal at `world_point`. Smooth triangles give their flat face
+    /// normal, since interpolating needs to know where they were hit. Groups and CSG
+    /// shapes give the normal of the primitive inside them that the point lies on.
     pub fn normal_at(&self, world_point: &Point) -> Vector {
-        let world_normal = match self {
-            Shape::Sphere(s) => {
-                let object_point = &s.properties.inverse_transform * world_point;
-                let object_normal = &object_point - &Point::origin();
-                &s.properties.inverse_transform.transpose() * &object_normal
+        self.world_normal(world_point, None)
+    }
+
+    pub(crate) fn normal_at_hit(&self, world_point: &Point, hit: &Intersection) -> Vector {
+        self.world_normal(world_point, Some((hit.u, hit.v)))
+    }
+
+    fn world_normal(&self, world_point: &Point, uv: Option<(f64, f64)>) -> Vector {
+        let properties = self.properties();
+        let object_point = &properties.inverse_transform * world_point;
+        let object_normal = match self {
+            Shape::Sphere(_) => &object_point - &Point::origin(),
+            Shape::Plane(_) => Vector::new(0.0, 1.0, 0.0),
+            Shape::Cube(_) => {
+                let (x, y, z) = (object_point.x(), object_point.y(), object_point.z());
+                let max_component = x.abs().max(y.abs()).max(z.abs());
+
+                if max_component == x.abs() {
+                    Vector::new(x, 0.0, 0.0)
+                } else if max_component == y.abs() {
+                    Vector::new(0.0, y, 0.0)
+                } else {
+                    Vector::new(0.0, 0.0, z)
+                }
+            }
+            Shape::Cylinder(cylinder) => {
+                let (x, y, z) = (object_point.x(), object_point.y(), object_point.z());
+                let distance = x.powi(2) + z.powi(2);
+
+                if distance < 1.0 && y >= cylinder.maximum - EPSILON {
+                    Vector::new(0.0, 1.0, 0.0)
+                } else if distance < 1.0 && y <= cylinder.minimum + EPSILON {
+                    Vector::new(0.0, -1.0, 0.0)
+                } else {
+                    Vector::new(x, 0.0, z)
+                }
             }
-            Shape::Plane(p) => {
-                let local_normal = Vector::new(0.0, 1.0, 0.0);
-                &p.properties.inverse_transform.transpose() * &local_normal
+            Shape::Cone(cone) => {
+                let (x, y, z) = (object_point.x(), object_point.y(), object_point.z());
+                let distance = x.powi(2) + z.powi(2);
+
+                if distance < y.powi(2) && y >= cone.maximum - EPSILON {
+                    Vector::new(0.0, 1.0, 0.0)
+                } else if distance < y.powi(2) && y <= cone.minimum + EPSILON {
+                    Vector::new(0.0, -1.0, 0.0)
+                } else {
+                    let radius = distance.sqrt();
+                    Vector::new(x, if y > 0.0 { -radius } else { radius }, z)
+                }
             }
+            Shape::Triangle(triangle) => triangle.normal,
+            Shape::SmoothTriangle(triangle) => match uv {
+                Some((u, v)) => {
+                    let n2 = &triangle.n2 * u;
+                    let n3 = &triangle.n3 * v;
+                    let n1 = &triangle.n1 * (1.0 - u - v);
+                    &(&n2 + &n3) + &n1
+                }
+                None => triangle.e2.cross(&triangle.e1),
+            },
+            Shape::Group(_) | Shape::Csg(_) => return self.child_normal(world_point),
         };
+        let world_normal = &properties.inverse_transform.transpose() * &object_normal;
         world_normal.normalize()
     }
 
-    pub fn colour_at(&self, world_point: &Point) -> Colour {
-        let properties = match self {
-            Shape::Sphere(s) => &s.properties,
-            Shape::Plane(p) => &p.properties,
+    // A point on a group or CSG shape lies on one of its primitives, which a ray along
+    // some axis through the point meets at t = 0. That primitive supplies the normal.
+    // A point off every child surface takes the nearest one those rays meet, and a
+    // composite with nothing along them has no normal to give.
+    fn child_normal(&self, world_point: &Point) -> Vector {
+        [
+            Vector::new(1.0, 0.0, 0.0),
+            Vector::new(0.0, 1.0, 0.0),
+            Vector::new(0.0, 0.0, 1.0),
+        ]
+        .into_iter()
+        .flat_map(|axis| self.intersect(&Ray::new(*world_point, axis)))
+        .min_by(|a, b| a.t.abs().total_cmp(&b.t.abs()))
+        .map_or(Vector::new(0.0, 0.0, 0.0), |hit| {
+            hit.shape.normal_at_hit(world_point, &hit)
+        })
+    }
+
+    pub fn bounds(&self) -> BoundingBox {
+        let object_bounds = match self {
+            Shape::Sphere(_) | Shape::Cube(_) => {
+                BoundingBox::new(Point::new(-1.0, -1.0, -1.0), Point::new(1.0, 1.0, 1.0))
+            }
+            Shape::Plane(_) => BoundingBox::new(
+                Point::new(f64::NEG_INFINITY, 0.0, f64::NEG_INFINITY),
+                Point::new(f64::INFINITY, 0.0, f64::INFINITY),
+            ),
+            Shape::Cylinder(cylinder) => BoundingBox::new(
+                Point::new(-1.0, cylinder.minimum, -1.0),
+                Point::new(1.0, cylinder.maximum, 1.0),
+            ),
+            Shape::Cone(cone) => {
+                let radius = cone.minimum.abs().max(cone.maximum.abs());
+                BoundingBox::new(
+                    Point::new(-radius, cone.minimum, -radius),
+                    Point::new(radius, cone.maximum, radius),
+                )
+            }
+            Shape::Triangle(triangle) => BoundingBox::empty()
+                .add_point(&triangle.p1)
+                .add_point(&triangle.p2)
+                .add_point(&triangle.p3),
+            Shape::SmoothTriangle(triangle) => BoundingBox::empty()
+                .add_point(&triangle.p1)
+                .add_point(&triangle.p2)
+                .add_point(&triangle.p3),
+            Shape::Group(group) => return group.bounds,
+            Shape::Csg(csg) => return csg.left.bounds().merge(&csg.right.bounds()),
         };
-        properties
+        object_bounds.transform(self.get_transform())
+    }
+
+    pub fn colour_at(&self, world_point: &Point) -> Colour {
+        self.properties()
             .material
             .pattern
             .colour_at_object(self, world_point)
     }
 
     pub fn get_material(&self) -> &Material {
-        let properties = match self {
+        &self.properties().material
+    }
+
+    pub fn get_transform(&self) -> &Matrix4 {
+        &self.properties().transform
+    }
+
+    pub fn get_inverse_transform(&self) -> &Matrix4 {
+        &self.properties().inverse_transform
+    }
+
+    fn properties(&self) -> &ShapeProperties {
+        match self {
             Shape::Sphere(s) => &s.properties,
             Shape::Plane(p) => &p.properties,
-        };
-        &properties.material
+            Shape::Cube(c) => &c.properties,
+            Shape::Cylinder(c) => &c.properties,
+            Shape::Cone(c) => &c.properties,
+            Shape::Triangle(t) => &t.properties,
+            Shape::SmoothTriangle(t) => &t.properties,
+            Shape::Group(g) => &g.properties,
+            Shape::Csg(c) => &c.properties,
+        }
     }
 
-    pub fn get_transform(&self) -> &Matrix4 {
+    fn properties_mut(&mut self) -> &mut ShapeProperties {
         match self {
-            Shape::Sphere(s) => &s.properties.transform,
-            Shape::Plane(p) => &p.properties.transform,
+            Shape::Sphere(s) => &mut s.properties,
+            Shape::Plane(p) => &mut p.properties,
+            Shape::Cube(c) => &mut c.properties,
+            Shape::Cylinder(c) => &mut c.properties,
+            Shape::Cone(c) => &mut c.properties,
+            Shape::Triangle(t) => &mut t.properties,
+            Shape::SmoothTriangle(t) => &mut t.properties,
+            Shape::Group(g) => &mut g.properties,
+            Shape::Csg(c) => &mut c.properties,
         }
     }
 
-    pub fn get_inverse_transform(&self) -> &Matrix4 {
+    // Folds a parent's transform into this shape (and any children) so that world/object
+    // conversions on a leaf shape account for every group it is nested in.
+    fn apply_parent_transform(&mut self, parent: &Matrix4) {
+        let properties = self.properties_mut();
+        properties.transform = parent * &properties.transform;
+        properties.inverse_transform = properties.transform.inverse();
+
+        match self {
+            Shape::Group(group) => {
+                for child in group.children.iter_mut() {
+                    child.apply_parent_transform(parent);
+                }
+                group.bounds = Group::children_bounds(&group.children);
+            }
+            Shape::Csg(csg) => {
+                csg.left.apply_parent_transform(parent);
+                csg.right.apply_parent_transform(parent);
+            }
+            _ => {}
+        }
+    }
+
+    fn includes(&self, other: &Shape) -> bool {
         match self {
-            Shape::Sphere(s) => &s.properties.inverse_transform,
-            Shape::Plane(p) => &p.properties.inverse_transform,
+            Shape::Group(group) => group.children.iter().any(|child| child.includes(other)),
+            Shape::Csg(csg) => csg.left.includes(other) || csg.right.includes(other),
+            _ => std::ptr::eq(self, other),
         }
     }
 }
 
+fn intersect_sides<'a>(
+    shape: &'a Shape,
+    ray: &Ray,
+    ts: &[f64],
+    minimum: f64,
+    maximum: f64,
+) -> Vec<Intersection<'a>> {
+    ts.iter()
+        .filter(|t| {
+            let y = ray.origin.y() + *t * ray.direction.y();
+            minimum < y && y < maximum
+        })
+        .map(|t| Intersection::new(*t, shape))
+        .collect()
+}
+
+fn intersect_caps<'a>(
+    shape: &'a Shape,
+    ray: &Ray,
+    minimum: f64,
+    maximum: f64,
+    radius_at: impl Fn(f64) -> f64,
+) -> Vec<Intersection<'a>> {
+    if ray.direction.y().abs() < EPSILON {
+        return vec![];
+    }
+
+    [minimum, maximum]
+        .iter()
+        .map(|y| ((y - ray.origin.y()) / ray.direction.y(), radius_at(*y)))
+        .filter(|(t, radius)| {
+            let x = ray.origin.x() + t * ray.direction.x();
+            let z = ray.origin.z() + t * ray.direction.z();
+            x.powi(2) + z.powi(2) <= radius.powi(2)
+        })
+        .map(|(t, _)| Intersection::new(t, shape))
+        .collect()
+}
+
+fn intersect_triangle<'a>(
+    shape: &'a Shape,
+    ray: &Ray,
+    p1: &Point,
+    e1: &Vector,
+    e2: &Vector,
+) -> Vec<Intersection<'a>> {
+    let dir_cross_e2 = ray.direction.cross(e2);
+    let determinant = e1.dot(&dir_cross_e2);
+    if determinant.abs() < EPSILON {
+        return vec![];
+    }
+
+    let f = 1.0 / determinant;
+    let p1_to_origin = &ray.origin - p1;
+    let u = f * p1_to_origin.dot(&dir_cross_e2);
+    if !(0.0..=1.0).contains(&u) {
+        return vec![];
+    }
+
+    let origin_cross_e1 = p1_to_origin.cross(e1);
+    let v = f * ray.direction.dot(&origin_cross_e1);
+    if v < 0.0 || u + v > 1.0 {
+        return vec![];
+    }
+
+    let t = f * e2.dot(&origin_cross_e1);
+    vec![Intersection::with_uv(t, shape, u, v)]
+}
+
+fn check_axis(origin: f64, direction: f64) -> (f64, f64) {
+    // A ray parallel to the slab either lies within it for all t or misses it entirely.
+    if direction.abs() < EPSILON {
+        return if (-1.0..=1.0).contains(&origin) {
+            (f64::NEG_INFINITY, f64::INFINITY)
+        } else {
+            (f64::INFINITY, f64::NEG_INFINITY)
+        };
+    }
+
+    let tmin = (-1.0 - origin) / direction;
+    let tmax = (1.0 - origin) / direction;
+
+    if tmin > tmax {
+        (tmax, tmin)
+    } else {
+        (tmin, tmax)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::Material;
@@ -195,6 +866,1019 @@ mod tests {
         }
     }
 
+    mod cube {
+        use super::*;
+        use crate::shape::Cube;
+
+        mod intersect {
+            use super::*;
+
+            #[test]
+            fn ray_intersects_each_face() {
+                let cube = Cube::new(Transform::default().build(), Material::default());
+                let cases = [
+                    (
+                        Point::new(5.0, 0.5, 0.0),
+                        Vector::new(-1.0, 0.0, 0.0),
+                        4.0,
+                        6.0,
+                    ),
+                    (
+                        Point::new(-5.0, 0.5, 0.0),
+                        Vector::new(1.0, 0.0, 0.0),
+                        4.0,
+                        6.0,
+                    ),
+                    (
+                        Point::new(0.5, 5.0, 0.0),
+                        Vector::new(0.0, -1.0, 0.0),
+                        4.0,
+                        6.0,
+                    ),
+                    (
+                        Point::new(0.5, -5.0, 0.0),
+                        Vector::new(0.0, 1.0, 0.0),
+                        4.0,
+                        6.0,
+                    ),
+                    (
+                        Point::new(0.5, 0.0, 5.0),
+                        Vector::new(0.0, 0.0, -1.0),
+                        4.0,
+                        6.0,
+                    ),
+                    (
+                        Point::new(0.5, 0.0, -5.0),
+                        Vector::new(0.0, 0.0, 1.0),
+                        4.0,
+                        6.0,
+                    ),
+                    (
+                        Point::new(0.0, 0.5, 0.0),
+                        Vector::new(0.0, 0.0, 1.0),
+                        -1.0,
+                        1.0,
+                    ),
+                ];
+
+                for (origin, direction, t1, t2) in cases {
+                    let xs = cube.intersect(&Ray::new(origin, direction));
+                    assert_eq!(xs.len(), 2);
+                    assert_eq!(xs[0].t, t1);
+                    assert_eq!(xs[1].t, t2);
+                }
+            }
+
+            #[test]
+            fn ray_misses() {
+                let cube = Cube::new(Transform::default().build(), Material::default());
+                let cases = [
+                    (
+                        Point::new(-2.0, 0.0, 0.0),
+                        Vector::new(0.2673, 0.5345, 0.8018),
+                    ),
+                    (
+                        Point::new(0.0, -2.0, 0.0),
+                        Vector::new(0.8018, 0.2673, 0.5345),
+                    ),
+                    (
+                        Point::new(0.0, 0.0, -2.0),
+                        Vector::new(0.5345, 0.8018, 0.2673),
+                    ),
+                    (Point::new(2.0, 0.0, 2.0), Vector::new(0.0, 0.0, -1.0)),
+                    (Point::new(0.0, 2.0, 2.0), Vector::new(0.0, -1.0, 0.0)),
+                    (Point::new(2.0, 2.0, 0.0), Vector::new(-1.0, 0.0, 0.0)),
+                ];
+
+                for (origin, direction) in cases {
+                    let xs = cube.intersect(&Ray::new(origin, direction));
+                    assert!(xs.is_empty());
+                }
+            }
+
+            #[test]
+            fn ray_parallel_to_a_face_it_lies_on() {
+                let cube = Cube::new(Transform::default().build(), Material::default());
+
+                let xs = cube.intersect(&Ray::new(
+                    Point::new(1.0, 0.0, -5.0),
+                    Vector::new(0.0, 0.0, 1.0),
+                ));
+
+                assert_eq!(xs.len(), 2);
+                assert_eq!(xs[0].t, 4.0);
+                assert_eq!(xs[1].t, 6.0);
+            }
+
+            #[test]
+            fn transformed() {
+                let cube = Cube::new(
+                    Transform::default()
+                        .scaling(2.0, 2.0, 2.0)
+                        .translation(0.0, 0.0, 1.0)
+                        .build(),
+                    Material::default(),
+                );
+
+                let xs = cube.intersect(&Ray::new(
+                    Point::new(0.0, 0.0, -5.0),
+                    Vector::new(0.0, 0.0, 1.0),
+                ));
+
+                assert_eq!(xs.len(), 2);
+                assert_eq!(xs[0].t, 4.0);
+                assert_eq!(xs[1].t, 8.0);
+            }
+        }
+
+        mod normal {
+            use super::*;
+
+            #[test]
+            fn surface_of_a_cube() {
+                let cube = Cube::new(Transform::default().build(), Material::default());
+                let cases = [
+                    (Point::new(1.0, 0.5, -0.8), Vector::new(1.0, 0.0, 0.0)),
+                    (Point::new(-1.0, -0.2, 0.9), Vector::new(-1.0, 0.0, 0.0)),
+                    (Point::new(-0.4, 1.0, -0.1), Vector::new(0.0, 1.0, 0.0)),
+                    (Point::new(0.3, -1.0, -0.7), Vector::new(0.0, -1.0, 0.0)),
+                    (Point::new(-0.6, 0.3, 1.0), Vector::new(0.0, 0.0, 1.0)),
+                    (Point::new(0.4, 0.4, -1.0), Vector::new(0.0, 0.0, -1.0)),
+                    (Point::new(1.0, 1.0, 1.0), Vector::new(1.0, 0.0, 0.0)),
+                    (Point::new(-1.0, -1.0, -1.0), Vector::new(-1.0, 0.0, 0.0)),
+                ];
+
+                for (point, normal) in cases {
+                    assert_eq!(cube.normal_at(&point), normal);
+                }
+            }
+
+            #[test]
+            fn rotated() {
+                let cube = Cube::new(
+                    Transform::default()
+                        .rotation_y(std::f64::consts::PI / 4.0)
+                        .build(),
+                    Material::default(),
+                );
+
+                let n = cube.normal_at(&Point::new(std::f64::consts::SQRT_2, 0.0, 0.0));
+
+                assert_eq!(
+                    n,
+                    Vector::new(
+                        std::f64::consts::FRAC_1_SQRT_2,
+                        0.0,
+                        -std::f64::consts::FRAC_1_SQRT_2
+                    )
+                );
+            }
+        }
+    }
+
+    mod cylinder {
+        use super::*;
+        use crate::shape::Cylinder;
+
+        mod intersect {
+            use super::*;
+
+            #[test]
+            fn ray_misses() {
+                let cylinder = Cylinder::new(Transform::default().build(), Material::default());
+                let cases = [
+                    (Point::new(1.0, 0.0, 0.0), Vector::new(0.0, 1.0, 0.0)),
+                    (Point::new(0.0, 0.0, 0.0), Vector::new(0.0, 1.0, 0.0)),
+                    (Point::new(0.0, 0.0, -5.0), Vector::new(1.0, 1.0, 1.0)),
+                ];
+
+                for (origin, direction) in cases {
+                    let xs = cylinder.intersect(&Ray::new(origin, direction.normalize()));
+                    assert!(xs.is_empty());
+                }
+            }
+
+            #[test]
+            fn ray_strikes() {
+                let cylinder = Cylinder::new(Transform::default().build(), Material::default());
+                let cases = [
+                    (
+                        Point::new(1.0, 0.0, -5.0),
+                        Vector::new(0.0, 0.0, 1.0),
+                        5.0,
+                        5.0,
+                    ),
+                    (
+                        Point::new(0.0, 0.0, -5.0),
+                        Vector::new(0.0, 0.0, 1.0),
+                        4.0,
+                        6.0,
+                    ),
+                    (
+                        Point::new(0.5, 0.0, -5.0),
+                        Vector::new(0.1, 1.0, 1.0),
+                        6.80798,
+                        7.08872,
+                    ),
+                ];
+
+                for (origin, direction, t0, t1) in cases {
+                    let xs = cylinder.intersect(&Ray::new(origin, direction.normalize()));
+                    assert_eq!(xs.len(), 2);
+                    assert!((xs[0].t - t0).abs() < 0.00001);
+                    assert!((xs[1].t - t1).abs() < 0.00001);
+                }
+            }
+
+            #[test]
+            fn truncated() {
+                let cylinder = Cylinder::truncated(
+                    Transform::default().build(),
+                    Material::default(),
+                    1.0,
+                    2.0,
+                    false,
+                );
+                let cases = [
+                    (Point::new(0.0, 1.5, 0.0), Vector::new(0.1, 1.0, 0.0), 0),
+                    (Point::new(0.0, 3.0, -5.0), Vector::new(0.0, 0.0, 1.0), 0),
+                    (Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0), 0),
+                    (Point::new(0.0, 2.0, -5.0), Vector::new(0.0, 0.0, 1.0), 0),
+                    (Point::new(0.0, 1.0, -5.0), Vector::new(0.0, 0.0, 1.0), 0),
+                    (Point::new(0.0, 1.5, -2.0), Vector::new(0.0, 0.0, 1.0), 2),
+                ];
+
+                for (origin, direction, count) in cases {
+                    let xs = cylinder.intersect(&Ray::new(origin, direction.normalize()));
+                    assert_eq!(xs.len(), count);
+                }
+            }
+
+            #[test]
+            fn closed() {
+                let cylinder = Cylinder::truncated(
+                    Transform::default().build(),
+                    Material::default(),
+                    1.0,
+                    2.0,
+                    true,
+                );
+                let cases = [
+                    (Point::new(0.0, 3.0, 0.0), Vector::new(0.0, -1.0, 0.0), 2),
+                    (Point::new(0.0, 3.0, -2.0), Vector::new(0.0, -1.0, 2.0), 2),
+                    (Point::new(0.0, 4.0, -2.0), Vector::new(0.0, -1.0, 1.0), 2),
+                    (Point::new(0.0, 0.0, -2.0), Vector::new(0.0, 1.0, 2.0), 2),
+                    (Point::new(0.0, -1.0, -2.0), Vector::new(0.0, 1.0, 1.0), 2),
+                ];
+
+                for (origin, direction, count) in cases {
+                    let xs = cylinder.intersect(&Ray::new(origin, direction.normalize()));
+                    assert_eq!(xs.len(), count);
+                }
+            }
+        }
+
+        mod normal {
+            use super::*;
+
+            #[test]
+            fn surface_of_a_cylinder() {
+                let cylinder = Cylinder::new(Transform::default().build(), Material::default());
+                let cases = [
+                    (Point::new(1.0, 0.0, 0.0), Vector::new(1.0, 0.0, 0.0)),
+                    (Point::new(0.0, 5.0, -1.0), Vector::new(0.0, 0.0, -1.0)),
+                    (Point::new(0.0, -2.0, 1.0), Vector::new(0.0, 0.0, 1.0)),
+                    (Point::new(-1.0, 1.0, 0.0), Vector::new(-1.0, 0.0, 0.0)),
+                ];
+
+                for (point, normal) in cases {
+                    assert_eq!(cylinder.normal_at(&point), normal);
+                }
+            }
+
+            #[test]
+            fn end_caps() {
+                let cylinder = Cylinder::truncated(
+                    Transform::default().build(),
+                    Material::default(),
+                    1.0,
+                    2.0,
+                    true,
+                );
+                let cases = [
+                    (Point::new(0.0, 1.0, 0.0), Vector::new(0.0, -1.0, 0.0)),
+                    (Point::new(0.5, 1.0, 0.0), Vector::new(0.0, -1.0, 0.0)),
+                    (Point::new(0.0, 1.0, 0.5), Vector::new(0.0, -1.0, 0.0)),
+                    (Point::new(0.0, 2.0, 0.0), Vector::new(0.0, 1.0, 0.0)),
+                    (Point::new(0.5, 2.0, 0.0), Vector::new(0.0, 1.0, 0.0)),
+                    (Point::new(0.0, 2.0, 0.5), Vector::new(0.0, 1.0, 0.0)),
+                ];
+
+                for (point, normal) in cases {
+                    assert_eq!(cylinder.normal_at(&point), normal);
+                }
+            }
+        }
+    }
+
+    mod cone {
+        use super::*;
+        use crate::shape::Cone;
+
+        mod intersect {
+            use super::*;
+
+            #[test]
+            fn ray_strikes() {
+                let cone = Cone::new(Transform::default().build(), Material::default());
+                let cases = [
+                    (
+                        Point::new(0.0, 0.0, -5.0),
+                        Vector::new(0.0, 0.0, 1.0),
+                        5.0,
+                        5.0,
+                    ),
+                    (
+                        Point::new(0.0, 0.0, -5.0),
+                        Vector::new(1.0, 1.0, 1.0),
+                        8.66025,
+                        8.66025,
+                    ),
+                    (
+                        Point::new(1.0, 1.0, -5.0),
+                        Vector::new(-0.5, -1.0, 1.0),
+                        4.55006,
+                        49.44994,
+                    ),
+                ];
+
+                for (origin, direction, t0, t1) in cases {
+                    let xs = cone.intersect(&Ray::new(origin, direction.normalize()));
+                    assert_eq!(xs.len(), 2);
+                    assert!((xs[0].t - t0).abs() < 0.0001);
+                    assert!((xs[1].t - t1).abs() < 0.0001);
+                }
+            }
+
+            #[test]
+            fn ray_parallel_to_one_half() {
+                let cone = Cone::new(Transform::default().build(), Material::default());
+
+                let xs = cone.intersect(&Ray::new(
+                    Point::new(0.0, 0.0, -1.0),
+                    Vector::new(0.0, 1.0, 1.0).normalize(),
+                ));
+
+                assert_eq!(xs.len(), 1);
+                assert!((xs[0].t - 0.35355).abs() < 0.00001);
+            }
+
+            #[test]
+            fn closed() {
+                let cone = Cone::truncated(
+                    Transform::default().build(),
+                    Material::default(),
+                    -0.5,
+                    0.5,
+                    true,
+                );
+                let cases = [
+                    (Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 1.0, 0.0), 0),
+                    (Point::new(0.0, 0.0, -0.25), Vector::new(0.0, 1.0, 1.0), 2),
+                    (Point::new(0.0, 0.0, -0.25), Vector::new(0.0, 1.0, 0.0), 4),
+                ];
+
+                for (origin, direction, count) in cases {
+                    let xs = cone.intersect(&Ray::new(origin, direction.normalize()));
+                    assert_eq!(xs.len(), count);
+                }
+            }
+        }
+
+        mod normal {
+            use super::*;
+
+            #[test]
+            fn surface_of_a_cone() {
+                let cone = Cone::new(Transform::default().build(), Material::default());
+                let cases = [
+                    (
+                        Point::new(1.0, 1.0, 1.0),
+                        Vector::new(1.0, -2.0_f64.sqrt(), 1.0),
+                    ),
+                    (Point::new(-1.0, -1.0, 0.0), Vector::new(-1.0, 1.0, 0.0)),
+                ];
+
+                for (point, normal) in cases {
+                    assert_eq!(cone.normal_at(&point), normal.normalize());
+                }
+            }
+
+            #[test]
+            fn end_caps() {
+                let cone = Cone::truncated(
+                    Transform::default().build(),
+                    Material::default(),
+                    -0.5,
+                    0.5,
+                    true,
+                );
+
+                assert_eq!(
+                    cone.normal_at(&Point::new(0.1, 0.5, 0.1)),
+                    Vector::new(0.0, 1.0, 0.0)
+                );
+                assert_eq!(
+                    cone.normal_at(&Point::new(0.1, -0.5, 0.1)),
+                    Vector::new(0.0, -1.0, 0.0)
+                );
+            }
+        }
+    }
+
+    mod triangle {
+        use super::*;
+        use crate::shape::Triangle;
+        use crate::Shape;
+
+        fn triangle() -> Shape {
+            Triangle::new(
+                Transform::default().build(),
+                Material::default(),
+                [
+                    Point::new(0.0, 1.0, 0.0),
+                    Point::new(-1.0, 0.0, 0.0),
+                    Point::new(1.0, 0.0, 0.0),
+                ],
+            )
+        }
+
+        #[test]
+        fn construction() {
+            if let Shape::Triangle(t) = triangle() {
+                assert_eq!(t.e1, Vector::new(-1.0, -1.0, 0.0));
+                assert_eq!(t.e2, Vector::new(1.0, -1.0, 0.0));
+                assert_eq!(t.normal, Vector::new(0.0, 0.0, -1.0));
+            } else {
+                panic!("expected a triangle");
+            }
+        }
+
+        mod intersect {
+            use super::*;
+
+            #[test]
+            fn ray_parallel() {
+                let triangle = triangle();
+                let xs = triangle.intersect(&Ray::new(
+                    Point::new(0.0, -1.0, -2.0),
+                    Vector::new(0.0, 1.0, 0.0),
+                ));
+                assert!(xs.is_empty());
+            }
+
+            #[test]
+            fn ray_misses_each_edge() {
+                let triangle = triangle();
+                let origins = [
+                    Point::new(1.0, 1.0, -2.0),
+                    Point::new(-1.0, 1.0, -2.0),
+                    Point::new(0.0, -1.0, -2.0),
+                ];
+
+                for origin in origins {
+                    let xs = triangle.intersect(&Ray::new(origin, Vector::new(0.0, 0.0, 1.0)));
+                    assert!(xs.is_empty());
+                }
+            }
+
+            #[test]
+            fn ray_strikes() {
+                let triangle = triangle();
+                let xs = triangle.intersect(&Ray::new(
+                    Point::new(0.0, 0.5, -2.0),
+                    Vector::new(0.0, 0.0, 1.0),
+                ));
+                assert_eq!(xs.len(), 1);
+                assert_eq!(xs[0].t, 2.0);
+            }
+        }
+
+        mod normal {
+            use super::*;
+
+            #[test]
+            fn constant_across_the_surface() {
+                let triangle = triangle();
+                let points = [
+                    Point::new(0.0, 0.5, 0.0),
+                    Point::new(-0.5, 0.75, 0.0),
+                    Point::new(0.5, 0.25, 0.0),
+                ];
+
+                for point in points {
+                    assert_eq!(triangle.normal_at(&point), Vector::new(0.0, 0.0, -1.0));
+                }
+            }
+        }
+    }
+
+    mod smooth_triangle {
+        use super::*;
+        use crate::shape::SmoothTriangle;
+        use crate::{Intersection, Shape};
+
+        fn smooth_triangle() -> Shape {
+            SmoothTriangle::new(
+                Transform::default().build(),
+                Material::default(),
+                [
+                    Point::new(0.0, 1.0, 0.0),
+                    Point::new(-1.0, 0.0, 0.0),
+                    Point::new(1.0, 0.0, 0.0),
+                ],
+                [
+                    Vector::new(0.0, 1.0, 0.0),
+                    Vector::new(-1.0, 0.0, 0.0),
+                    Vector::new(1.0, 0.0, 0.0),
+                ],
+            )
+        }
+
+        #[test]
+        fn intersection_stores_u_and_v() {
+            let triangle = smooth_triangle();
+            let xs = triangle.intersect(&Ray::new(
+                Point::new(-0.2, 0.3, -2.0),
+                Vector::new(0.0, 0.0, 1.0),
+            ));
+
+            assert_eq!(xs.len(), 1);
+            assert!((xs[0].u - 0.45).abs() < 0.00001);
+            assert!((xs[0].v - 0.25).abs() < 0.00001);
+        }
+
+        #[test]
+        fn normal_interpolated_with_u_and_v() {
+            let triangle = smooth_triangle();
+            let hit = Intersection::with_uv(1.0, &triangle, 0.45, 0.25);
+
+            assert_eq!(
+                triangle.normal_at_hit(&Point::new(0.0, 0.0, 0.0), &hit),
+                Vector::new(-0.5547, 0.83205, 0.0)
+            );
+        }
+        #[test]
+        fn normal_without_a_hit_is_the_face_normal() {
+            assert_eq!(
+                smooth_triangle().normal_at(&Point::new(0.0, 0.0, 0.0)),
+                Vector::new(0.0, 0.0, -1.0)
+            );
+        }
+    }
+
+    mod group {
+        use super::*;
+        use crate::shape::{Group, Sphere};
+        use crate::Shape;
+
+        fn children(shape: &Shape) -> &Vec<Shape> {
+            match shape {
+                Shape::Group(group) => &group.children,
+                _ => panic!("expected a group"),
+            }
+        }
+
+        #[test]
+        fn empty_group() {
+            let group = Group::new(Transform::default().build(), vec![]);
+            assert!(children(&group).is_empty());
+
+            let xs = group.intersect(&Ray::new(
+                Point::new(0.0, 0.0, 0.0),
+                Vector::new(0.0, 0.0, 1.0),
+            ));
+            assert!(xs.is_empty());
+        }
+
+        #[test]
+        fn intersect_nonempty_group() {
+            let group = Group::new(
+                Transform::default().build(),
+                vec![
+                    Sphere::new(Transform::default().build(), Material::default()),
+                    Sphere::new(
+                        Transform::default().translation(0.0, 0.0, -3.0).build(),
+                        Material::default(),
+                    ),
+                    Sphere::new(
+                        Transform::default().translation(5.0, 0.0, 0.0).build(),
+                        Material::default(),
+                    ),
+                ],
+            );
+            let s1 = &children(&group)[0];
+            let s2 = &children(&group)[1];
+
+            let xs = group.intersect(&Ray::new(
+                Point::new(0.0, 0.0, -5.0),
+                Vector::new(0.0, 0.0, 1.0),
+            ));
+
+            assert_eq!(xs.len(), 4);
+            assert!(std::ptr::eq(xs[0].shape, s2));
+            assert!(std::ptr::eq(xs[1].shape, s2));
+            assert!(std::ptr::eq(xs[2].shape, s1));
+            assert!(std::ptr::eq(xs[3].shape, s1));
+        }
+
+        #[test]
+        fn intersect_transformed_group() {
+            let group = Group::new(
+                Transform::default().scaling(2.0, 2.0, 2.0).build(),
+                vec![Sphere::new(
+                    Transform::default().translation(5.0, 0.0, 0.0).build(),
+                    Material::default(),
+                )],
+            );
+
+            let xs = group.intersect(&Ray::new(
+                Point::new(10.0, 0.0, -10.0),
+                Vector::new(0.0, 0.0, 1.0),
+            ));
+
+            assert_eq!(xs.len(), 2);
+        }
+
+        #[test]
+        fn child_transform_includes_parents() {
+            let group = Group::new(
+                Transform::default()
+                    .rotation_y(std::f64::consts::PI / 2.0)
+                    .build(),
+                vec![Group::new(
+                    Transform::default().scaling(2.0, 2.0, 2.0).build(),
+                    vec![Sphere::new(
+                        Transform::default().translation(5.0, 0.0, 0.0).build(),
+                        Material::default(),
+                    )],
+                )],
+            );
+            let sphere = &children(&children(&group)[0])[0];
+
+            assert_eq!(
+                sphere.get_inverse_transform() * &Point::new(-2.0, 0.0, -10.0),
+                Point::new(0.0, 0.0, -1.0)
+            );
+        }
+
+        #[test]
+        fn normal_on_a_child_object() {
+            let group = Group::new(
+                Transform::default()
+                    .rotation_y(std::f64::consts::PI / 2.0)
+                    .build(),
+                vec![Group::new(
+                    Transform::default().scaling(1.0, 2.0, 3.0).build(),
+                    vec![Sphere::new(
+                        Transform::default().translation(5.0, 0.0, 0.0).build(),
+                        Material::default(),
+                    )],
+                )],
+            );
+            let sphere = &children(&children(&group)[0])[0];
+
+            assert_eq!(
+                sphere.normal_at(&Point::new(1.7321, 1.1547, -5.5774)),
+                Vector::new(0.28570, 0.42854, -0.85716)
+            );
+        }
+        #[test]
+        fn group_normal_comes_from_the_child_under_the_point() {
+            let group = Group::new(
+                Transform::default().translation(0.0, 2.0, 0.0).build(),
+                vec![
+                    Sphere::new(Transform::default().build(), Material::default()),
+                    Sphere::new(
+                        Transform::default().translation(5.0, 0.0, 0.0).build(),
+                        Material::default(),
+                    ),
+                ],
+            );
+
+            assert_eq!(
+                group.normal_at(&Point::new(6.0, 2.0, 0.0)),
+                Vector::new(1.0, 0.0, 0.0)
+            );
+            assert_eq!(
+                group.normal_at(&Point::new(0.0, 3.0, 0.0)),
+                Vector::new(0.0, 1.0, 0.0)
+            );
+        }
+
+        #[test]
+        fn empty_group_has_no_normal() {
+            let group = Group::new(Transform::default().build(), vec![]);
+
+            assert_eq!(
+                group.normal_at(&Point::new(0.0, 0.0, 0.0)),
+                Vector::new(0.0, 0.0, 0.0)
+            );
+        }
+    }
+
+    mod csg {
+        use super::*;
+        use crate::shape::{Csg, CsgOperation, Cube, Cylinder, Sphere};
+        use crate::{Intersection, Shape};
+
+        fn operands(shape: &Shape) -> (&Shape, &Shape) {
+            match shape {
+                Shape::Csg(csg) => (&csg.left, &csg.right),
+                _ => panic!("expected a csg"),
+            }
+        }
+
+        #[test]
+        fn normal_comes_from_the_operand_under_the_point() {
+            let csg = Csg::new(
+                Transform::default().build(),
+                CsgOperation::Difference,
+                Cube::new(Transform::default().build(), Material::default()),
+                Sphere::new(
+                    Transform::default().translation(0.0, 1.0, 0.0).build(),
+                    Material::default(),
+                ),
+            );
+
+            assert_eq!(
+                csg.normal_at(&Point::new(1.0, -0.5, 0.5)),
+                Vector::new(1.0, 0.0, 0.0)
+            );
+            // The floor of the carved-out hollow is the sphere's surface, with its normal.
+            assert_eq!(
+                csg.normal_at(&Point::new(0.0, 0.0, 0.0)),
+                Vector::new(0.0, -1.0, 0.0)
+            );
+        }
+
+        #[test]
+        fn operation_rules() {
+            let cases = [
+                (
+                    CsgOperation::Union,
+                    [false, true, false, true, false, false, true, true],
+                ),
+                (
+                    CsgOperation::Intersection,
+                    [true, false, true, false, true, true, false, false],
+                ),
+                (
+                    CsgOperation::Difference,
+                    [false, true, false, true, true, true, false, false],
+                ),
+            ];
+
+            for (operation, expected) in cases {
+                let mut i = 0;
+                for left_hit in [true, false] {
+                    for in_left in [true, false] {
+                        for in_right in [true, false] {
+                            assert_eq!(
+                                operation.allows(left_hit, in_left, in_right),
+                                expected[i],
+                                "{:?} lhit={} inl={} inr={}",
+                                operation,
+                                left_hit,
+                                in_left,
+                                in_right
+                            );
+                            i += 1;
+                        }
+                    }
+                }
+            }
+        }
+
+        #[test]
+        fn filtering_a_list_of_intersections() {
+            let cases = [
+                (CsgOperation::Union, 0, 3),
+                (CsgOperation::Intersection, 1, 2),
+                (CsgOperation::Difference, 0, 1),
+            ];
+
+            for (operation, x0, x1) in cases {
+                let shape = Csg::new(
+                    Transform::default().build(),
+                    operation,
+                    Sphere::new(Transform::default().build(), Material::default()),
+                    Cube::new(Transform::default().build(), Material::default()),
+                );
+                let (s1, s2) = operands(&shape);
+                let xs = vec![
+                    Intersection::new(1.0, s1),
+                    Intersection::new(2.0, s2),
+                    Intersection::new(3.0, s1),
+                    Intersection::new(4.0, s2),
+                ];
+
+                let result = match &shape {
+                    Shape::Csg(csg) => csg.filter_intersections(xs.clone()),
+                    _ => unreachable!(),
+                };
+
+                assert_eq!(result.len(), 2);
+                assert_eq!(result[0].t, xs[x0].t);
+                assert_eq!(result[1].t, xs[x1].t);
+            }
+        }
+
+        #[test]
+        fn ray_misses() {
+            let shape = Csg::new(
+                Transform::default().build(),
+                CsgOperation::Union,
+                Sphere::new(Transform::default().build(), Material::default()),
+                Cube::new(Transform::default().build(), Material::default()),
+            );
+
+            let xs = shape.intersect(&Ray::new(
+                Point::new(0.0, 2.0, -5.0),
+                Vector::new(0.0, 0.0, 1.0),
+            ));
+            assert!(xs.is_empty());
+        }
+
+        #[test]
+        fn ray_hits() {
+            let shape = Csg::new(
+                Transform::default().build(),
+                CsgOperation::Union,
+                Sphere::new(Transform::default().build(), Material::default()),
+                Sphere::new(
+                    Transform::default().translation(0.0, 0.0, 0.5).build(),
+                    Material::default(),
+                ),
+            );
+            let (s1, s2) = operands(&shape);
+
+            let xs = shape.intersect(&Ray::new(
+                Point::new(0.0, 0.0, -5.0),
+                Vector::new(0.0, 0.0, 1.0),
+            ));
+
+            assert_eq!(xs.len(), 2);
+            assert_eq!(xs[0].t, 4.0);
+            assert!(std::ptr::eq(xs[0].shape, s1));
+            assert_eq!(xs[1].t, 6.5);
+            assert!(std::ptr::eq(xs[1].shape, s2));
+        }
+
+        #[test]
+        fn difference_carves_a_hole() {
+            let shape = Csg::new(
+                Transform::default().build(),
+                CsgOperation::Difference,
+                Cube::new(Transform::default().build(), Material::default()),
+                Cylinder::truncated(
+                    Transform::default().scaling(0.5, 1.0, 0.5).build(),
+                    Material::default(),
+                    -2.0,
+                    2.0,
+                    true,
+                ),
+            );
+
+            let through_hole = shape.intersect(&Ray::new(
+                Point::new(0.0, 5.0, 0.0),
+                Vector::new(0.0, -1.0, 0.0),
+            ));
+            assert!(through_hole.is_empty());
+
+            let through_wall = shape.intersect(&Ray::new(
+                Point::new(0.75, 5.0, 0.0),
+                Vector::new(0.0, -1.0, 0.0),
+            ));
+            assert_eq!(through_wall.len(), 2);
+        }
+    }
+
+    mod bounds {
+        use super::*;
+        use crate::shape::{Cone, Cylinder, Group, Plane, Sphere, Triangle};
+
+        #[test]
+        fn transformed_sphere() {
+            let sphere = Sphere::new(
+                Transform::default()
+                    .scaling(2.0, 2.0, 2.0)
+                    .translation(1.0, 0.0, 0.0)
+                    .build(),
+                Material::default(),
+            );
+
+            let bounds = sphere.bounds();
+            assert_eq!(bounds.min, Point::new(-1.0, -2.0, -2.0));
+            assert_eq!(bounds.max, Point::new(3.0, 2.0, 2.0));
+        }
+
+        #[test]
+        fn plane_is_unbounded() {
+            let plane = Plane::new(Transform::default().build(), Material::default());
+            assert!(!plane.bounds().is_finite());
+        }
+
+        #[test]
+        fn truncated_cylinder_and_cone() {
+            let cylinder = Cylinder::truncated(
+                Transform::default().build(),
+                Material::default(),
+                -5.0,
+                3.0,
+                true,
+            );
+            assert_eq!(cylinder.bounds().min, Point::new(-1.0, -5.0, -1.0));
+            assert_eq!(cylinder.bounds().max, Point::new(1.0, 3.0, 1.0));
+
+            let cone = Cone::truncated(
+                Transform::default().build(),
+                Material::default(),
+                -5.0,
+                3.0,
+                true,
+            );
+            assert_eq!(cone.bounds().min, Point::new(-5.0, -5.0, -5.0));
+            assert_eq!(cone.bounds().max, Point::new(5.0, 3.0, 5.0));
+
+            let infinite = Cylinder::new(Transform::default().build(), Material::default());
+            assert!(!infinite.bounds().is_finite());
+        }
+
+        #[test]
+        fn triangle() {
+            let triangle = Triangle::new(
+                Transform::default().build(),
+                Material::default(),
+                [
+                    Point::new(-3.0, 7.0, 2.0),
+                    Point::new(6.0, 2.0, -4.0),
+                    Point::new(2.0, -1.0, -1.0),
+                ],
+            );
+
+            assert_eq!(triangle.bounds().min, Point::new(-3.0, -1.0, -4.0));
+            assert_eq!(triangle.bounds().max, Point::new(6.0, 7.0, 2.0));
+        }
+
+        #[test]
+        fn group_contains_children() {
+            let group = Group::new(
+                Transform::default().translation(0.0, 10.0, 0.0).build(),
+                vec![
+                    Sphere::new(
+                        Transform::default()
+                            .scaling(2.0, 2.0, 2.0)
+                            .translation(2.0, 5.0, -3.0)
+                            .build(),
+                        Material::default(),
+                    ),
+                    Cylinder::truncated(
+                        Transform::default()
+                            .scaling(0.5, 1.0, 0.5)
+                            .translation(-4.0, -1.0, 4.0)
+                            .build(),
+                        Material::default(),
+                        -2.0,
+                        2.0,
+                        false,
+                    ),
+                ],
+            );
+
+            let bounds = group.bounds();
+            assert_eq!(bounds.min, Point::new(-4.5, 7.0, -5.0));
+            assert_eq!(bounds.max, Point::new(4.0, 17.0, 4.5));
+        }
+
+        #[test]
+        fn group_skips_children_when_bounds_missed() {
+            let group = Group::new(
+                Transform::default().build(),
+                vec![Sphere::new(
+                    Transform::default().translation(0.0, 0.0, 5.0).build(),
+                    Material::default(),
+                )],
+            );
+
+            let xs = group.intersect(&Ray::new(
+                Point::new(5.0, 5.0, -5.0),
+                Vector::new(0.0, 0.0, 1.0),
+            ));
+            assert!(xs.is_empty());
+        }
+    }
+
     mod sphere {
         use super::*;
         use crate::shape::Sphere;
@@ -362,6 +2046,7 @@ mod tests {
             }
 
             #[test]
+            #[allow(clippy::approx_constant)]
             fn translated() {
                 assert_eq!(
                     Sphere::new(